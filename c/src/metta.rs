@@ -1,9 +1,10 @@
 use hyperon::Atom;
 use hyperon::metta::text::*;
 use hyperon::metta::interpreter;
-use hyperon::metta::interpreter::InterpretedAtom;
+use hyperon::metta::interpreter::{InterpretedAtom, InterpreterError, CancellationToken};
 use hyperon::common::plan::StepResult;
 use hyperon::metta::runner::Metta;
+use hyperon::metta::runner::capabilities::Capability;
 
 use crate::util::*;
 use crate::atom::*;
@@ -99,6 +100,32 @@ pub unsafe extern "C" fn validate_atom(space: *const grounding_space_t, atom: *c
     hyperon::metta::types::validate_atom((*space).borrow().deref(), &(*atom).atom)
 }
 
+/// Returns null when `atom` has `typ`, or the type error description atom
+/// (see `hyperon::metta::types::TypeError::as_atom`) otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn check_type_detailed(space: *const grounding_space_t, atom: *const atom_t, typ: *const atom_t) -> *mut atom_t {
+    hyperon::metta::types::check_type_detailed((*space).borrow().deref(), &(*atom).atom, &(*typ).atom)
+        .map_or_else(|err| atom_into_ptr(err.as_atom()), |()| std::ptr::null_mut())
+}
+
+/// Returns null when `atom` is typed correctly, or the type error
+/// description atom (see `hyperon::metta::types::TypeError::as_atom`)
+/// otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn validate_atom_detailed(space: *const grounding_space_t, atom: *const atom_t) -> *mut atom_t {
+    hyperon::metta::types::validate_atom_detailed((*space).borrow().deref(), &(*atom).atom)
+        .map_or_else(|err| atom_into_ptr(err.as_atom()), |()| std::ptr::null_mut())
+}
+
+/// Same as `validate_atom_detailed` but atoms without declared type are not
+/// accepted where specific type is expected (see
+/// `hyperon::metta::types::validate_atom_strict`).
+#[no_mangle]
+pub unsafe extern "C" fn validate_atom_strict(space: *const grounding_space_t, atom: *const atom_t) -> *mut atom_t {
+    hyperon::metta::types::validate_atom_strict((*space).borrow().deref(), &(*atom).atom)
+        .map_or_else(|err| atom_into_ptr(err.as_atom()), |()| std::ptr::null_mut())
+}
+
 #[no_mangle]
 pub extern "C" fn get_atom_types(space: *const grounding_space_t, atom: *const atom_t,
         callback: c_atoms_callback_t, context: *mut c_void) {
@@ -111,7 +138,7 @@ pub extern "C" fn get_atom_types(space: *const grounding_space_t, atom: *const a
 // MeTTa interpreter API
 
 pub struct step_result_t<'a> {
-    result: StepResult<'a, Vec<InterpretedAtom>, (Atom, Atom)>,
+    result: StepResult<'a, Vec<InterpretedAtom>, InterpreterError>,
 }
 
 #[no_mangle]
@@ -122,6 +149,26 @@ pub extern "C" fn interpret_init<'a>(space: *mut grounding_space_t, expr: *const
     Box::into_raw(Box::new(step_result_t{ result: step }))
 }
 
+#[no_mangle]
+pub extern "C" fn interpret_init_with_derivation<'a>(space: *mut grounding_space_t, expr: *const atom_t) -> *mut step_result_t<'a> {
+    let space = unsafe{ &(*space) };
+    let expr = unsafe{ &(*expr) };
+    let options = interpreter::InterpreterOptions{ derivation: true, ..Default::default() };
+    let step = interpreter::interpret_init_with_options(space.shared(), &expr.atom, options);
+    Box::into_raw(Box::new(step_result_t{ result: step }))
+}
+
+#[no_mangle]
+pub extern "C" fn interpret_init_with_cancellation<'a>(space: *mut grounding_space_t, expr: *const atom_t,
+        token: *const cancellation_token_t) -> *mut step_result_t<'a> {
+    let space = unsafe{ &(*space) };
+    let expr = unsafe{ &(*expr) };
+    let token = unsafe{ &(*token) };
+    let options = interpreter::InterpreterOptions{ cancellation: Some(token.token.clone()), ..Default::default() };
+    let step = interpreter::interpret_init_with_options(space.shared(), &expr.atom, options);
+    Box::into_raw(Box::new(step_result_t{ result: step }))
+}
+
 #[no_mangle]
 pub extern "C" fn interpret_step(step: *mut step_result_t) -> *mut step_result_t {
     let step = unsafe { Box::from_raw(step) };
@@ -148,6 +195,27 @@ pub extern "C" fn step_get_result(step: *mut step_result_t,
     }
 }
 
+/// Returns results of the interpretation paired with their derivations.
+/// Each result is returned as `(<result> (Derivation ...))` expression.
+/// Derivation is empty unless interpretation was started using
+/// `interpret_init_with_derivation`.
+#[no_mangle]
+pub extern "C" fn step_get_result_with_derivation(step: *mut step_result_t,
+        callback: c_atoms_callback_t, context: *mut c_void) {
+    let step = unsafe{ Box::from_raw(step) };
+    match step.result {
+        StepResult::Return(mut res) => {
+            let res = res.drain(0..).map(|res| {
+                let derivation = res.derivation().as_atom();
+                Atom::expr([res.into_tuple().0, derivation])
+            }).collect();
+            return_atoms(&res, callback, context);
+        },
+        StepResult::Error(_) => return_atoms(&vec![], callback, context),
+        _ => panic!("Not expected step result: {:?}", step.result),
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn step_to_str(step: *const step_result_t, callback: c_str_callback_t, context: *mut c_void) {
     let result = unsafe{ &(*step).result };
@@ -215,3 +283,122 @@ pub extern "C" fn metta_load_module(metta: *mut metta_t, name: *const c_char) {
     metta.load_module(PathBuf::from(cstr_as_str(name)))
         .expect("Returning errors from C API is not implemented yet");
 }
+
+/// Type checks the module and passes the list of type errors to the `output`
+/// callback. When module cannot be read the message is passed to the `error`
+/// callback and `false` is returned. Expressions to be executed including
+/// `!(import! ...)` are skipped, thus declarations of the imported modules
+/// are not visible to the check.
+#[no_mangle]
+pub extern "C" fn metta_check_module(metta: *mut metta_t, path: *const c_char,
+        output: c_atoms_callback_t, out_context: *mut c_void,
+        error: c_str_callback_t, err_context: *mut c_void) -> bool {
+    let metta = unsafe{ &*metta }.borrow();
+    match metta.check_module(PathBuf::from(cstr_as_str(path))) {
+        Ok(errors) => {
+            return_atoms(&errors, output, out_context);
+            true
+        },
+        Err(message) => {
+            error(str_as_cstr(message.as_str()).as_ptr(), err_context);
+            false
+        },
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn metta_profile_report(metta: *mut metta_t) -> *mut atom_t {
+    let metta = unsafe{ &*metta }.borrow();
+    let report = metta.profiler().borrow().as_atom();
+    atom_into_ptr(report)
+}
+
+#[no_mangle]
+pub extern "C" fn metta_profile_reset(metta: *mut metta_t) {
+    let metta = unsafe{ &*metta }.borrow();
+    metta.profiler().borrow_mut().reset();
+}
+
+#[repr(C)]
+pub enum capability_t {
+    FILE_SYSTEM,
+    CONSOLE,
+    MODULE_LOADING,
+    STATE_MUTATION,
+    SPACE_MUTATION,
+}
+
+impl From<capability_t> for Capability {
+    fn from(capability: capability_t) -> Self {
+        match capability {
+            capability_t::FILE_SYSTEM => Capability::FileSystem,
+            capability_t::CONSOLE => Capability::Console,
+            capability_t::MODULE_LOADING => Capability::ModuleLoading,
+            capability_t::STATE_MUTATION => Capability::StateMutation,
+            capability_t::SPACE_MUTATION => Capability::SpaceMutation,
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn metta_set_capability(metta: *mut metta_t, capability: capability_t, enabled: bool) {
+    let metta = unsafe{ &*metta }.borrow();
+    metta.capabilities().borrow_mut().set(capability.into(), enabled);
+}
+
+#[no_mangle]
+pub extern "C" fn metta_is_capability_enabled(metta: *mut metta_t, capability: capability_t) -> bool {
+    let metta = unsafe{ &*metta }.borrow();
+    metta.capabilities().borrow().is_enabled(capability.into())
+}
+
+/// Sets directory outside of which file system operations are not
+/// permitted, NULL `root` removes the restriction.
+#[no_mangle]
+pub extern "C" fn metta_set_sandbox_root(metta: *mut metta_t, root: *const c_char) {
+    let metta = unsafe{ &*metta }.borrow();
+    let root = if root.is_null() { None } else { Some(PathBuf::from(cstr_as_str(root))) };
+    metta.capabilities().borrow_mut().set_sandbox_root(root);
+}
+
+// Cancellation token can be passed to another thread or used from a signal
+// handler to stop the interpretation.
+
+pub struct cancellation_token_t {
+    token: CancellationToken,
+}
+
+#[no_mangle]
+pub extern "C" fn cancellation_token_new() -> *mut cancellation_token_t {
+    Box::into_raw(Box::new(cancellation_token_t{ token: CancellationToken::new() }))
+}
+
+#[no_mangle]
+pub extern "C" fn cancellation_token_free(token: *mut cancellation_token_t) {
+    unsafe{ drop(Box::from_raw(token)) }
+}
+
+#[no_mangle]
+pub extern "C" fn cancellation_token_cancel(token: *const cancellation_token_t) {
+    unsafe{ &(*token) }.token.cancel();
+}
+
+#[no_mangle]
+pub extern "C" fn cancellation_token_reset(token: *const cancellation_token_t) {
+    unsafe{ &(*token) }.token.reset();
+}
+
+#[no_mangle]
+pub extern "C" fn cancellation_token_is_cancelled(token: *const cancellation_token_t) -> bool {
+    unsafe{ &(*token) }.token.is_cancelled()
+}
+
+/// Returns new handle of the token which cancels interpretation run by the
+/// `metta` instance. The caller owns the returned handle and must free it
+/// using `cancellation_token_free()`, freeing the handle doesn't affect the
+/// `metta` instance.
+#[no_mangle]
+pub extern "C" fn metta_cancellation_token(metta: *mut metta_t) -> *mut cancellation_token_t {
+    let metta = unsafe{ &*metta }.borrow();
+    Box::into_raw(Box::new(cancellation_token_t{ token: metta.cancellation_token() }))
+}
@@ -38,6 +38,31 @@ START_TEST (test_validate_atom)
 }
 END_TEST
 
+START_TEST (test_validate_atom_detailed)
+{
+    grounding_space_t* space = grounding_space_new();
+    grounding_space_add(space, expr(atom_sym(":"), atom_sym("a"), atom_sym("A"), 0));
+    grounding_space_add(space, expr(atom_sym(":"), atom_sym("b"), atom_sym("B"), 0));
+    grounding_space_add(space, expr(atom_sym(":"), atom_sym("foo"), expr(atom_sym("->"), atom_sym("A"), atom_sym("B"), 0), 0));
+
+    atom_t* foo_a = expr(atom_sym("foo"), atom_sym("a"), 0);
+    ck_assert(validate_atom_detailed(space, foo_a) == 0);
+    atom_free(foo_a);
+
+    atom_t* foo_b = expr(atom_sym("foo"), atom_sym("b"), 0);
+    atom_t* error = validate_atom_detailed(space, foo_b);
+    atom_t* expected = expr(atom_sym("BadFunctionCall"), expr(atom_sym("foo"), atom_sym("b"), 0),
+        expr(atom_sym("Candidate"), expr(atom_sym("->"), atom_sym("A"), atom_sym("B"), 0),
+            expr(atom_sym("BadArgType"), atom_sym("1"),
+                expr(atom_sym("Expected"), atom_sym("A"), 0),
+                expr(atom_sym("Actual"), atom_sym("B"), 0), 0), 0), 0);
+    ck_assert(atom_eq(error, expected));
+    atom_free(expected);
+    atom_free(error);
+    atom_free(foo_b);
+}
+END_TEST
+
 typedef struct _atoms_t {
     atom_t** items;
     size_t size;
@@ -94,6 +119,7 @@ void init_test(TCase* test_case) {
     tcase_add_checked_fixture(test_case, setup, teardown);
     tcase_add_test(test_case, test_check_type);
     tcase_add_test(test_case, test_validate_atom);
+    tcase_add_test(test_case, test_validate_atom_detailed);
     tcase_add_test(test_case, test_get_atom_types);
 }
 
@@ -0,0 +1,89 @@
+//! Implementation of the `#[derive(Grounded)]` macro.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Expr, ExprLit, Ident, Lit, LitStr};
+
+use crate::sexpr;
+
+#[derive(Default)]
+struct Attributes {
+    type_: Option<TokenStream>,
+    display: Option<LitStr>,
+    match_: Option<Ident>,
+    match_by_equality: bool,
+    execute: Option<Ident>,
+}
+
+fn parse_attributes(input: &DeriveInput) -> syn::Result<Attributes> {
+    let mut attrs = Attributes::default();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("grounded")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type") {
+                attrs.type_ = Some(match meta.value()?.parse::<Expr>()? {
+                    Expr::Lit(ExprLit{ lit: Lit::Str(text), .. }) =>
+                        sexpr::parse_single(&text.value(), text.span())?,
+                    expr => quote!(#expr),
+                });
+            } else if meta.path.is_ident("display") {
+                attrs.display = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("match_by_equality") {
+                attrs.match_by_equality = true;
+            } else if meta.path.is_ident("match_") {
+                attrs.match_ = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("execute") {
+                attrs.execute = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error("unsupported grounded attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    if attrs.match_by_equality && attrs.match_.is_some() {
+        return Err(syn::Error::new_spanned(&input.ident,
+            "match_by_equality and match_ attributes cannot be used together"));
+    }
+    Ok(attrs)
+}
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = parse_attributes(&input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let type_ = attrs.type_.unwrap_or_else(|| quote!(::hyperon::rust_type_atom::<Self>()));
+    let execute = match attrs.execute {
+        Some(method) => quote!(self.#method(args)),
+        None => quote!({ let _ = args; ::hyperon::execute_not_executable(self) }),
+    };
+    let match_ = match attrs.match_ {
+        Some(method) => quote!(self.#method(other)),
+        None => quote!(::hyperon::match_by_equality(self, other)),
+    };
+    let display = attrs.display.map(|display| quote! {
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, "{}", #display)
+            }
+        }
+    });
+
+    Ok(quote! {
+        #display
+
+        impl #impl_generics ::hyperon::Grounded for #name #ty_generics #where_clause {
+            fn type_(&self) -> ::hyperon::Atom {
+                #type_
+            }
+
+            fn execute(&self, args: &mut ::std::vec::Vec<::hyperon::Atom>)
+                -> ::std::result::Result<::std::vec::Vec<::hyperon::Atom>, ::hyperon::ExecError> {
+                #execute
+            }
+
+            fn match_(&self, other: &::hyperon::Atom) -> ::hyperon::matcher::MatchResultIter {
+                #match_
+            }
+        }
+    })
+}
@@ -0,0 +1,61 @@
+//! Procedural macros of the `hyperon` crate. They are re-exported by
+//! `hyperon`, use them via `hyperon::Grounded` instead of depending on this
+//! crate directly.
+
+extern crate proc_macro;
+
+mod sexpr;
+mod grounded;
+mod metta;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implements `hyperon::Grounded` trait for the type. Type should implement
+/// `Debug`, `Clone` and `PartialEq` to be wrapped into `hyperon::Atom` via
+/// `Atom::gnd()`. Behaviour is configured by `#[grounded(...)]` attributes:
+/// - `type = "(-> Number Number)"` - MeTTa type of the atom, it is either a
+///   string which is parsed as an atom or Rust expression which returns
+///   `Atom`; by default it is the name of the Rust type;
+/// - `display = "name"` - implement `Display` which writes `name`; by
+///   default `Display` should be implemented manually;
+/// - `match_by_equality` - match atom with other atoms of the same type by
+///   equality, this is the default behaviour;
+/// - `match_ = "method"` - match atom using `fn method(&self, other: &Atom)
+///   -> MatchResultIter` method;
+/// - `execute = "method"` - execute atom using `fn method(&self, args: &mut
+///   Vec<Atom>) -> Result<Vec<Atom>, ExecError>` method; by default atom is
+///   not executable.
+#[proc_macro_derive(Grounded, attributes(grounded))]
+pub fn derive_grounded(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match grounded::derive(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Parses MeTTa atoms at compile time. Returns `hyperon::Atom` when single
+/// atom is passed and `Vec<hyperon::Atom>` otherwise. Tokens which are not
+/// separated by whitespace are glued into a single symbol, thus `foo-bar`
+/// and `->` are parsed as symbols. When the compiler doesn't provide source
+/// positions of the tokens only punctuation is glued, see `Token::is_adjacent`.
+/// Tokens starting from `$` are variables.
+/// Integer, float and string literals are converted into `Number` and
+/// `String` grounded atoms, other grounded atoms are not parsed because there
+/// is no tokenizer at compile time. Rust expression in
+/// braces `{...}` is converted into atom using
+/// `hyperon::metta::runner::typed::GroundedValue`, thus it can be either
+/// `Atom` or a Rust value mapped to the MeTTa type like `i64`. MeTTa `;`
+/// comments are not supported, use Rust comments instead.
+#[proc_macro]
+pub fn metta(input: TokenStream) -> TokenStream {
+    let result = metta::parse(input).map(|mut atoms| match atoms.len() {
+        1 => atoms.pop().unwrap(),
+        _ => quote::quote!(::std::vec![ #(#atoms),* ] as ::std::vec::Vec<::hyperon::Atom>),
+    });
+    match result {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,132 @@
+//! Implementation of the `metta!` macro.
+
+use proc_macro::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Lit;
+
+/// Collects sequence of the adjacent tokens into a single MeTTa token. Rust
+/// tokenizer splits MeTTa symbols like `->` or `foo-bar` into separate tokens
+/// thus tokens are glued back when there is no whitespace between them.
+struct Token {
+    text: String,
+    span: Span,
+    /// Span of the last Rust token glued into this token.
+    last: Span,
+    /// Last Rust token is a punctuation which is joint with the next token
+    /// or `$` sign of the variable.
+    joint: bool,
+}
+
+impl Token {
+    /// Positions of the spans are compared only when both spans have source
+    /// text. Proc-macro servers like rust-analyzer may pass spans with dummy
+    /// positions, in such case only joint punctuation (like `->`) and `$`
+    /// are glued with the next token, thus `foo-bar` is split into three
+    /// symbols.
+    fn is_adjacent(&self, next: &Span) -> bool {
+        if self.last.source_text().is_some() && next.source_text().is_some() {
+            let (end, next) = (self.last.end(), next.start());
+            end.line() == next.line() && end.column() == next.column()
+        } else {
+            self.joint
+        }
+    }
+
+    fn to_tokens(&self) -> syn::Result<TokenStream2> {
+        let text = &self.text;
+        if let Some(value) = self.literal()? {
+            return Ok(quote!(::hyperon::metta::runner::typed::GroundedValue::into_atom(#value)));
+        }
+        match text.strip_prefix('$') {
+            Some("") => Err(syn::Error::new(self.span.into(), "Variable name is expected after $")),
+            Some(name) => Ok(quote!(::hyperon::Atom::var(#name))),
+            None => Ok(quote!(::hyperon::Atom::sym(#text))),
+        }
+    }
+
+    /// Returns Rust value of the integer, float or string literal optionally
+    /// prefixed by `-`, values are converted into `Number` and `String`
+    /// grounded atoms the same way the runner's tokenizer does.
+    fn literal(&self) -> syn::Result<Option<TokenStream2>> {
+        let error = |msg: &str| Err(syn::Error::new(self.span.into(), msg));
+        let (sign, text) = match self.text.strip_prefix('-') {
+            Some(text) => ("-", text),
+            None => ("", self.text.as_str()),
+        };
+        let lit = match syn::parse_str::<Lit>(text) {
+            Ok(Lit::Bool(_)) | Err(_) => return Ok(None),
+            Ok(lit) => lit,
+        };
+        if !lit.suffix().is_empty() {
+            return error("Literal suffixes are not supported");
+        }
+        match lit {
+            Lit::Int(int) => match format!("{}{}", sign, int.base10_digits()).parse::<i64>() {
+                Ok(value) => Ok(Some(quote!(#value))),
+                Err(_) => error("Integer literal is out of range of i64"),
+            },
+            Lit::Float(float) => match format!("{}{}", sign, float.base10_digits()).parse::<f64>() {
+                Ok(value) => Ok(Some(quote!(#value))),
+                Err(_) => error("Incorrect float literal"),
+            },
+            Lit::Str(string) if sign.is_empty() => {
+                let value = string.value();
+                Ok(Some(quote!(::std::string::String::from(#value))))
+            },
+            Lit::Str(_) => Ok(None),
+            _ => error("Only integer, float and string literals are supported"),
+        }
+    }
+}
+
+/// Parses sequence of atoms, returns Rust code constructing each atom.
+pub fn parse(input: TokenStream) -> syn::Result<Vec<TokenStream2>> {
+    let mut atoms = Vec::new();
+    let mut token: Option<Token> = None;
+    for tree in input {
+        let span = tree.span();
+        let joint = matches!(&tree, TokenTree::Punct(punct)
+            if punct.spacing() == Spacing::Joint || punct.as_char() == '$');
+        let text = match tree {
+            TokenTree::Group(group) => {
+                if let Some(token) = token.take() {
+                    atoms.push(token.to_tokens()?);
+                }
+                match group.delimiter() {
+                    Delimiter::Parenthesis => {
+                        let children = parse(group.stream())?;
+                        atoms.push(quote!(::hyperon::Atom::expr(vec![ #(#children),* ])));
+                    },
+                    Delimiter::Brace => {
+                        let expr: TokenStream2 = group.stream().into();
+                        atoms.push(quote!(::hyperon::metta::runner::typed::GroundedValue::into_atom({ #expr })));
+                    },
+                    _ => return Err(syn::Error::new(span.into(),
+                        "Only (...) expressions and {...} Rust expressions are expected")),
+                }
+                continue;
+            },
+            TokenTree::Ident(ident) => ident.to_string(),
+            TokenTree::Punct(punct) => punct.as_char().to_string(),
+            TokenTree::Literal(literal) => literal.to_string(),
+        };
+        match token {
+            Some(ref mut prev) if prev.is_adjacent(&span) => {
+                prev.text.push_str(&text);
+                prev.last = span;
+                prev.joint = joint;
+            },
+            _ => {
+                if let Some(prev) = token.take() {
+                    atoms.push(prev.to_tokens()?);
+                }
+                token = Some(Token{ text, span, last: span, joint });
+            },
+        }
+    }
+    if let Some(token) = token {
+        atoms.push(token.to_tokens()?);
+    }
+    Ok(atoms)
+}
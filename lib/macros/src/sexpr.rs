@@ -0,0 +1,92 @@
+//! Compile time parser of the MeTTa S-expressions. Converts text into the
+//! Rust code which constructs corresponding [hyperon::Atom].
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// Parsed S-expression. Grounded atoms cannot be parsed without tokenizer,
+/// thus all non-variable tokens are kept as symbols.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SExpr {
+    Symbol(String),
+    Variable(String),
+    Expression(Vec<SExpr>),
+}
+
+impl SExpr {
+    /// Returns Rust code constructing the atom.
+    pub fn to_tokens(&self) -> TokenStream {
+        match self {
+            SExpr::Symbol(name) => quote!(::hyperon::Atom::sym(#name)),
+            SExpr::Variable(name) => quote!(::hyperon::Atom::var(#name)),
+            SExpr::Expression(children) => {
+                let children = children.iter().map(SExpr::to_tokens);
+                quote!(::hyperon::Atom::expr(vec![ #(#children),* ]))
+            },
+        }
+    }
+}
+
+/// Parses all S-expressions from `text`.
+pub fn parse(text: &str) -> Result<Vec<SExpr>, String> {
+    let mut chars = text.chars().peekable();
+    let mut stack: Vec<Vec<SExpr>> = vec![vec![]];
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+            c if c.is_whitespace() => {},
+            '(' => stack.push(vec![]),
+            ')' => {
+                let children = stack.pop().filter(|_| !stack.is_empty())
+                    .ok_or_else(|| "Unexpected right bracket".to_string())?;
+                stack.last_mut().unwrap().push(SExpr::Expression(children));
+            },
+            _ => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '(' && *c != ')') {
+                    token.push(c);
+                }
+                let atom = match token.strip_prefix('$') {
+                    Some("") => return Err("Variable name is expected after $".into()),
+                    Some(name) => SExpr::Variable(name.into()),
+                    None => SExpr::Symbol(token),
+                };
+                stack.last_mut().unwrap().push(atom);
+            },
+        }
+    }
+    match stack.len() {
+        1 => Ok(stack.pop().unwrap()),
+        _ => Err("Unexpected end of expression".into()),
+    }
+}
+
+/// Parses single S-expression from `text` and returns Rust code constructing
+/// it, errors are reported at `span`.
+pub fn parse_single(text: &str, span: Span) -> syn::Result<TokenStream> {
+    let mut atoms = parse(text).map_err(|err| syn::Error::new(span, err))?;
+    match atoms.len() {
+        1 => Ok(atoms.pop().unwrap().to_tokens()),
+        n => Err(syn::Error::new(span, format!("Single atom is expected, {} found", n))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str) -> SExpr {
+        SExpr::Symbol(name.into())
+    }
+
+    #[test]
+    fn parse_sexpr() {
+        assert_eq!(parse("(-> $t (List $t)) ; comment\n a"), Ok(vec![
+            SExpr::Expression(vec![sym("->"), SExpr::Variable("t".into()),
+                SExpr::Expression(vec![sym("List"), SExpr::Variable("t".into())])]),
+            sym("a")]));
+        assert_eq!(parse("(a"), Err("Unexpected end of expression".into()));
+        assert_eq!(parse("a)"), Err("Unexpected right bracket".into()));
+        assert_eq!(parse("$ a"), Err("Variable name is expected after $".into()));
+    }
+}
@@ -1,5 +1,7 @@
 #[macro_use]
 extern crate mopa;
+// allows using the hyperon_macros inside the crate
+extern crate self as hyperon;
 
 pub mod atom;
 pub mod common;
@@ -7,6 +9,7 @@ pub mod space;
 pub mod metta;
 
 pub use atom::*;
+pub use hyperon_macros::{Grounded, metta};
 
 use ctor::ctor;
 
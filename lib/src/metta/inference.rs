@@ -0,0 +1,452 @@
+//! Infers types of the functions which are defined by `=` rules only and
+//! have no `:` declaration in the space.
+//!
+//! Each function gets a signature `(-> $t1 ... $tN $r)` with fresh type
+//! variables where `N` is an arity of the rules. Then both sides of each rule
+//! are typed using types of the symbols and grounded atoms they contain and
+//! the variables of the signature are unified with the types found. Types of
+//! the rule variables are unified across all occurrences in the rule. After
+//! all rules are processed the signature with the bindings applied is a
+//! principal type of the function. Type variables which are left unbound
+//! mean the function is polymorphic. When some rule cannot be typed
+//! consistently with the previous ones the conflict is reported.
+//!
+//! Subtyping is taken into account only for symbols which have `:<` super
+//! types declared, like [get_atom_types] does.
+
+use crate::*;
+use crate::atom::matcher::{Bindings, apply_bindings_to_atom, apply_bindings_to_bindings, match_atoms};
+use crate::space::Space;
+use super::*;
+use super::types::{get_atom_types, get_arg_types, is_func};
+
+use std::collections::HashMap;
+
+pub const TYPE_CONFLICT_SYMBOL : Atom = sym!("TypeConflict");
+pub const INFERRED_SYMBOL : Atom = sym!("Inferred");
+pub const NO_RULES_SYMBOL : Atom = sym!("NoRules");
+
+/// Reason why the type of the function cannot be inferred.
+#[derive(Clone, PartialEq, Debug)]
+pub enum InferenceError {
+    /// There are no `=` rules which define the function.
+    NoRules,
+    /// `rule` cannot be typed consistently with the rules processed before
+    /// it, `inferred` contains types inferred from the previous rules.
+    Conflict{ rule: Atom, inferred: Vec<Atom> },
+}
+
+impl InferenceError {
+    /// Represents the error as a MeTTa expression, for instance
+    /// `(TypeConflict (= (f) b) (Inferred (-> A)))`.
+    pub fn as_atom(&self) -> Atom {
+        match self {
+            InferenceError::NoRules => NO_RULES_SYMBOL,
+            InferenceError::Conflict{ rule, inferred } => {
+                let mut inferred_atom = vec![INFERRED_SYMBOL];
+                inferred_atom.extend(inferred.iter().cloned());
+                Atom::expr([TYPE_CONFLICT_SYMBOL, rule.clone(), Atom::expr(inferred_atom)])
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_atom())
+    }
+}
+
+fn fresh_type_var() -> Atom {
+    Atom::Variable(VariableAtom::new("t").make_unique())
+}
+
+fn replace_undefined_types(typ: &Atom) -> Atom {
+    let mut typ = make_variables_unique(typ);
+    typ.iter_mut().filter(|atom| **atom == ATOM_TYPE_UNDEFINED)
+        .for_each(|atom| *atom = fresh_type_var());
+    typ
+}
+
+fn is_meta_type(typ: &Atom) -> bool {
+    *typ == ATOM_TYPE_ATOM || *typ == ATOM_TYPE_SYMBOL || *typ == ATOM_TYPE_VARIABLE
+        || *typ == ATOM_TYPE_GROUNDED || *typ == ATOM_TYPE_EXPRESSION
+}
+
+fn check_meta_type(atom: &Atom, typ: &Atom) -> bool {
+    match atom {
+        _ if *typ == ATOM_TYPE_ATOM => true,
+        // value of the variable is known at runtime only
+        Atom::Variable(_) => true,
+        Atom::Symbol(_) => *typ == ATOM_TYPE_SYMBOL,
+        Atom::Grounded(_) => *typ == ATOM_TYPE_GROUNDED,
+        Atom::Expression(_) => *typ == ATOM_TYPE_EXPRESSION,
+    }
+}
+
+fn unify(left: &Atom, right: &Atom, bindings: &Bindings) -> Option<Bindings> {
+    let left = apply_bindings_to_atom(left, bindings);
+    let right = apply_bindings_to_atom(right, bindings);
+    let matched = match_atoms(&left, &right).next();
+    matched.and_then(|matched| apply_bindings_to_bindings(&matched, bindings).ok())
+}
+
+/// Applies bindings to the type. Variables which are bound to each other
+/// only are replaced by a single fresh variable, because
+/// [Bindings::resolve] returns an arbitrary variable of such group.
+fn resolve_type(typ: &Atom, bindings: &Bindings) -> Atom {
+    let mut bindings = bindings.clone();
+    loop {
+        let resolved = apply_bindings_to_atom(typ, &bindings);
+        let free = resolved.iter().filter_map(AtomIter::extract_var)
+            .find(|var| matches!(bindings.resolve(var), Some(Atom::Variable(_))))
+            .cloned();
+        match free {
+            Some(var) => { bindings.add_var_binding(var, fresh_type_var()); },
+            None => return resolved,
+        }
+    }
+}
+
+/// Renames type variables in order of appearance to make inferred types
+/// readable and comparable.
+fn normalize_type(typ: &Atom) -> Atom {
+    let mut vars: HashMap<VariableAtom, Atom> = HashMap::new();
+    let mut typ = typ.clone();
+    typ.iter_mut().for_each(|atom| if let Atom::Variable(var) = atom {
+        let next = vars.len() + 1;
+        *atom = vars.entry(var.clone())
+            .or_insert_with(|| Atom::Variable(VariableAtom::new(format!("t{}", next))))
+            .clone();
+    });
+    typ
+}
+
+fn split_rule(rule: &Atom) -> Option<(&Atom, &Atom)> {
+    match rule {
+        Atom::Expression(expr) => match expr.children().as_slice() {
+            [eq, left, right] if *eq == EQUAL_SYMBOL => Some((left, right)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn rule_head(rule: &Atom) -> Option<(&Atom, usize)> {
+    match split_rule(rule) {
+        Some((Atom::Expression(left), _)) => match left.children().as_slice() {
+            [head @ Atom::Symbol(_), args @ ..] => Some((head, args.len())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn query_rules(space: &dyn Space) -> Vec<Atom> {
+    let var_l = Atom::Variable(VariableAtom::new("l").make_unique());
+    let var_r = Atom::Variable(VariableAtom::new("r").make_unique());
+    let rule = Atom::expr([EQUAL_SYMBOL, var_l, var_r]);
+    space.subst(&rule, &rule)
+}
+
+struct Inference<'a> {
+    space: &'a dyn Space,
+    known: &'a [(Atom, Vec<Atom>)],
+    function: &'a Atom,
+    signature: Atom,
+    vars: HashMap<VariableAtom, Atom>,
+}
+
+impl<'a> Inference<'a> {
+    fn new(space: &'a dyn Space, known: &'a [(Atom, Vec<Atom>)], function: &'a Atom, arity: usize) -> Self {
+        let mut signature = vec![ARROW_SYMBOL];
+        signature.extend((0..=arity).map(|_| fresh_type_var()));
+        Self{ space, known, function, signature: Atom::expr(signature), vars: HashMap::new() }
+    }
+
+    fn atom_types(&mut self, atom: &Atom) -> Vec<Atom> {
+        match atom {
+            Atom::Variable(var) => vec![self.vars.entry(var.clone())
+                .or_insert_with(fresh_type_var).clone()],
+            _ if atom == self.function => vec![self.signature.clone()],
+            _ => {
+                let known = self.known.iter().find(|(function, _)| function == atom);
+                let types = match known {
+                    Some((_, types)) => types.clone(),
+                    None => get_atom_types(self.space, atom),
+                };
+                let types: Vec<Atom> = types.iter()
+                    .filter(|typ| **typ != ATOM_TYPE_UNDEFINED)
+                    .map(replace_undefined_types).collect();
+                if types.is_empty() {
+                    vec![fresh_type_var()]
+                } else {
+                    types
+                }
+            },
+        }
+    }
+
+    fn infer(&mut self, atom: &Atom, bindings: &Bindings) -> Vec<(Atom, Bindings)> {
+        let children = match atom {
+            Atom::Expression(expr) if !expr.children().is_empty() => expr.children(),
+            Atom::Expression(_) => return vec![(fresh_type_var(), bindings.clone())],
+            _ => return self.atom_types(atom).drain(0..)
+                .map(|typ| (typ, bindings.clone())).collect(),
+        };
+        let op = &children[0];
+        let args = &children[1..];
+        let op_types = match op {
+            Atom::Expression(_) => self.infer(op, bindings),
+            _ => self.atom_types(op).drain(0..).map(|typ| (typ, bindings.clone())).collect(),
+        };
+        let mut result = Vec::new();
+        for (op_type, bindings) in op_types {
+            let op_type = apply_bindings_to_atom(&op_type, &bindings);
+            if let Atom::Variable(_) = op_type {
+                // unknown operation is a function of the arguments passed
+                let mut fn_type = vec![ARROW_SYMBOL];
+                fn_type.extend((0..=args.len()).map(|_| fresh_type_var()));
+                let fn_type = Atom::expr(fn_type);
+                if let Some(bindings) = unify(&op_type, &fn_type, &bindings) {
+                    result.extend(self.infer_call(&fn_type, args, bindings));
+                }
+            } else if is_func(&op_type) {
+                result.extend(self.infer_call(&op_type, args, bindings));
+            } else {
+                result.extend(self.infer_tuple(op_type, args, bindings));
+            }
+        }
+        result
+    }
+
+    fn infer_call(&mut self, fn_type: &Atom, args: &[Atom], bindings: Bindings) -> Vec<(Atom, Bindings)> {
+        let (arg_types, ret_type) = get_arg_types(fn_type);
+        if arg_types.len() != args.len() {
+            return vec![];
+        }
+        let mut states = vec![bindings];
+        for (arg, expected) in args.iter().zip(arg_types) {
+            let mut next = Vec::new();
+            for bindings in states {
+                if is_meta_type(expected) {
+                    if check_meta_type(arg, expected) {
+                        next.push(bindings);
+                    }
+                } else {
+                    for (actual, bindings) in self.infer(arg, &bindings) {
+                        next.extend(unify(&actual, expected, &bindings));
+                    }
+                }
+            }
+            states = next;
+        }
+        states.drain(0..).map(|bindings| (ret_type.clone(), bindings)).collect()
+    }
+
+    fn infer_tuple(&mut self, op_type: Atom, args: &[Atom], bindings: Bindings) -> Vec<(Atom, Bindings)> {
+        let mut states = vec![(vec![op_type], bindings)];
+        for arg in args {
+            let mut next = Vec::new();
+            for (types, bindings) in states {
+                for (typ, bindings) in self.infer(arg, &bindings) {
+                    let mut types = types.clone();
+                    types.push(typ);
+                    next.push((types, bindings));
+                }
+            }
+            states = next;
+        }
+        states.drain(0..).map(|(types, bindings)| (Atom::expr(types), bindings)).collect()
+    }
+
+    fn infer_rule(&mut self, rule: &Atom, bindings: &Bindings) -> Vec<Bindings> {
+        let (left, right) = split_rule(rule).expect("Rule is expected");
+        self.vars.clear();
+        let mut result = Vec::new();
+        for (left_type, bindings) in self.infer(left, bindings) {
+            for (right_type, bindings) in self.infer(right, &bindings) {
+                result.extend(unify(&left_type, &right_type, &bindings));
+            }
+        }
+        result
+    }
+
+    fn types(&self, alternatives: &[Bindings]) -> Vec<Atom> {
+        let mut types: Vec<Atom> = Vec::new();
+        for bindings in alternatives {
+            let typ = normalize_type(&resolve_type(&self.signature, bindings));
+            if !types.contains(&typ) {
+                types.push(typ);
+            }
+        }
+        types
+    }
+}
+
+fn infer_function_types_with_known(space: &dyn Space, known: &[(Atom, Vec<Atom>)],
+        function: &Atom, rules: &[Atom]) -> Result<Vec<Atom>, InferenceError> {
+    let arity = match rules.first().and_then(rule_head) {
+        Some((_, arity)) => arity,
+        None => return Err(InferenceError::NoRules),
+    };
+    let mut inference = Inference::new(space, known, function, arity);
+    let mut alternatives = vec![Bindings::new()];
+    for rule in rules {
+        let next: Vec<Bindings> = alternatives.iter()
+            .flat_map(|bindings| inference.infer_rule(rule, bindings)).collect();
+        if next.is_empty() {
+            return Err(InferenceError::Conflict{ rule: rule.clone(),
+                inferred: inference.types(&alternatives) });
+        }
+        alternatives = next;
+    }
+    Ok(inference.types(&alternatives))
+}
+
+fn function_rules(rules: &[Atom], function: &Atom) -> Vec<Atom> {
+    rules.iter().filter(|rule| rule_head(rule).map_or(false, |(head, _)| head == function))
+        .cloned().collect()
+}
+
+/// Infers types of the `function` from the `=` rules which define it in the
+/// `space`. Returns the list of principal types found. More than one type is
+/// returned when the function uses overloaded symbols. Type variables in the
+/// result are named `$t1`, `$t2`, etc.
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::expr;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::inference::infer_function_types;
+///
+/// let space = metta_space("
+///     (: foo (-> A B))
+///     (= (bar $x) (foo $x))
+/// ");
+///
+/// assert_eq!(infer_function_types(&space, &expr!("bar")), Ok(vec![expr!("->" "A" "B")]));
+/// ```
+pub fn infer_function_types(space: &dyn Space, function: &Atom) -> Result<Vec<Atom>, InferenceError> {
+    let rules = function_rules(&query_rules(space), function);
+    infer_function_types_with_known(space, &[], function, &rules)
+}
+
+/// Infers types of all functions which are defined by `=` rules in the
+/// `space` and have no type declared. Types inferred for one function are
+/// used to infer types of the functions which call it. Returns results
+/// sorted by function name.
+pub fn infer_types(space: &dyn Space) -> Vec<(Atom, Result<Vec<Atom>, InferenceError>)> {
+    let rules = query_rules(space);
+    let mut functions: Vec<Atom> = Vec::new();
+    rules.iter().filter_map(rule_head).for_each(|(head, _)| {
+        if !functions.contains(head) && get_atom_types(space, head) == vec![ATOM_TYPE_UNDEFINED] {
+            functions.push(head.clone());
+        }
+    });
+    functions.sort_by_key(|function| function.to_string());
+    let rules: Vec<(Atom, Vec<Atom>)> = functions.drain(0..)
+        .map(|function| { let rules = function_rules(&rules, &function); (function, rules) })
+        .collect();
+
+    let mut known = Vec::new();
+    let mut results = Vec::new();
+    // each pass propagates types one more call level up, thus number of
+    // passes is limited by number of functions
+    for _ in 0..=rules.len() {
+        let next: Vec<(Atom, Result<Vec<Atom>, InferenceError>)> = rules.iter()
+            .map(|(function, rules)| (function.clone(),
+                infer_function_types_with_known(space, &known, function, rules)))
+            .collect();
+        if next == results {
+            break;
+        }
+        known = next.iter().filter_map(|(function, types)|
+            types.as_ref().ok().map(|types| (function.clone(), types.clone()))).collect();
+        results = next;
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metta::metta_space;
+    use crate::atom::matcher::atoms_are_equivalent;
+
+    #[test]
+    fn infer_polymorphic_function() {
+        let space = metta_space("(= (id $x) $x)");
+
+        let types = infer_function_types(&space, &expr!("id")).unwrap();
+
+        assert_eq!(types.len(), 1);
+        assert!(atoms_are_equivalent(&types[0], &expr!("->" t t)));
+    }
+
+    #[test]
+    fn infer_recursive_function() {
+        let space = metta_space("
+            (: Z Nat)
+            (: S (-> Nat Nat))
+            (= (add Z $y) $y)
+            (= (add (S $x) $y) (S (add $x $y)))
+        ");
+
+        assert_eq!(infer_function_types(&space, &expr!("add")),
+            Ok(vec![expr!("->" "Nat" "Nat" "Nat")]));
+    }
+
+    #[test]
+    fn infer_parameterized_types() {
+        let space = metta_space("
+            (: Nil (List $t))
+            (: Cons (-> $t (List $t) (List $t)))
+            (: a A)
+            (= (singleton $x) (Cons $x Nil))
+            (= (list-a) (singleton a))
+        ");
+
+        let types = infer_function_types(&space, &expr!("singleton")).unwrap();
+        assert_eq!(types.len(), 1);
+        assert!(atoms_are_equivalent(&types[0], &expr!("->" t ("List" t))));
+    }
+
+    #[test]
+    fn infer_reports_conflict() {
+        let space = metta_space("
+            (: a A)
+            (: b B)
+            (= (f) a)
+            (= (f) b)
+        ");
+
+        let error = infer_function_types(&space, &expr!("f")).unwrap_err();
+        match error {
+            InferenceError::Conflict{ rule, inferred } => {
+                assert!(rule == expr!("=" ("f") "a") || rule == expr!("=" ("f") "b"));
+                assert!(inferred == vec![expr!("->" "A")] || inferred == vec![expr!("->" "B")]);
+            },
+            _ => panic!("Conflict is expected, found: {}", error),
+        }
+        assert_eq!(infer_function_types(&space, &expr!("g")), Err(InferenceError::NoRules));
+    }
+
+    #[test]
+    fn infer_types_uses_inferred_types() {
+        let space = metta_space("
+            (: foo (-> A B))
+            (: a A)
+            (= (baz $x) (bar $x))
+            (= (bar $x) (foo $x))
+            (= (typed) a)
+            (: typed (-> A))
+        ");
+
+        assert_eq!(infer_types(&space), vec![
+            (expr!("bar"), Ok(vec![expr!("->" "A" "B")])),
+            (expr!("baz"), Ok(vec![expr!("->" "A" "B")])),
+        ]);
+    }
+}
@@ -4,6 +4,7 @@
 //!
 //! For an atom and type on input (when type is not set `Undefined` is used):
 //! * [Atom::Variable] is returned as is.
+//! * `(Error ...)` expression is returned as is.
 //! * [Atom::Symbol] and [Atom::Grounded] are type checked; each applicable
 //!   type of the atom is checked vs expected type:
 //!   * If type is corrent then atom is returned as is.
@@ -38,6 +39,10 @@
 //!     is returned.
 //!
 //! Call the expression:
+//! * If operation type is a function and one of the arguments is
+//!   interpreted into `(Error ...)` expression then this error is returned
+//!   instead of calling the expression, thus errors are propagated to the
+//!   enclosing function calls.
 //! * If there is a cached result for this expression then return it
 //! * If operation is instance of [Atom::Grounded] then operation is executed:
 //!   * If result is error then error is returned
@@ -63,6 +68,13 @@
 //!   or all alternative interpretations are errors; the overall result includes
 //!   successfuly interpreted alternatives only
 //! * call of the expression returns either succesful result or original expression
+//!
+//! When [InterpreterOptions::max_results] is set the alternatives which
+//! results are the results of the whole interpretation (alternative types
+//! of the operation, reducted arguments of the interpreted expression, results
+//! of its matching or execution and so on recursively) are not interpreted
+//! after enough results are calculated. Alternatives of the arguments are
+//! interpreted completely because their results can be filtered out later.
 
 use crate::*;
 use crate::common::plan::*;
@@ -73,19 +85,161 @@ use crate::space::grounding::*;
 use crate::common::collections::ListMap;
 use crate::metta::*;
 use crate::metta::types::{is_func, get_arg_types, get_type_bindings,
-    get_atom_types, match_reducted_types};
-use crate::common::shared::LockBorrow;
+    get_atom_types, match_subtype};
+use crate::common::shared::{Shared, LockBorrow};
+use crate::metta::profiler::Profiler;
 
 use std::ops::Deref;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::fmt::{Debug, Display, Formatter};
 
+/// Single step of the atom's derivation.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DerivationStep {
+    /// Expression is reduced using `=` rule from the atomspace. `rule` is an
+    /// instance of the rule applied, `bindings` are variable bindings found
+    /// by the query.
+    Rule{ expr: Atom, rule: Atom, bindings: Bindings },
+    /// Expression is reduced by executing grounded operation.
+    Grounded{ expr: Atom, result: Atom },
+    /// Argument of the expression with the given `index` is interpreted.
+    Argument{ index: usize, derivation: Derivation },
+}
+
+pub const DERIVATION_SYMBOL : Atom = sym!("Derivation");
+pub const RULE_STEP_SYMBOL : Atom = sym!("Rule");
+pub const GROUNDED_STEP_SYMBOL : Atom = sym!("Grounded");
+pub const ARGUMENT_STEP_SYMBOL : Atom = sym!("Argument");
+pub const BINDINGS_SYMBOL : Atom = sym!("Bindings");
+
+impl DerivationStep {
+    /// Represents the step as a MeTTa expression.
+    pub fn as_atom(&self) -> Atom {
+        match self {
+            DerivationStep::Rule{ expr, rule, bindings } => {
+                let mut bindings_atom = vec![BINDINGS_SYMBOL];
+                bindings_atom.extend(bindings.iter()
+                    .map(|(var, value)| Atom::expr([Atom::Variable(var.clone()), value])));
+                Atom::expr([RULE_STEP_SYMBOL, expr.clone(), rule.clone(), Atom::expr(bindings_atom)])
+            },
+            DerivationStep::Grounded{ expr, result } =>
+                Atom::expr([GROUNDED_STEP_SYMBOL, expr.clone(), result.clone()]),
+            DerivationStep::Argument{ index, derivation } =>
+                Atom::expr([ARGUMENT_STEP_SYMBOL, Atom::sym(index.to_string()), derivation.as_atom()]),
+        }
+    }
+}
+
+/// Derivation tree of the interpretation result. It is a sequence of steps
+/// which were applied to the original atom to get the result. Steps which
+/// interpret arguments of the expression contain derivations of the
+/// arguments. Derivation is collected only when
+/// [InterpreterOptions::derivation] is set.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Derivation(Vec<Rc<DerivationStep>>);
+
+impl Derivation {
+    /// Returns steps of the derivation in order of application.
+    pub fn steps(&self) -> impl Iterator<Item=&DerivationStep> {
+        self.0.iter().map(|step| step.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn with_step(&self, step: DerivationStep) -> Self {
+        let mut steps = self.0.clone();
+        steps.push(Rc::new(step));
+        Self(steps)
+    }
+
+    /// Represents the derivation as a MeTTa expression which can be shown
+    /// to the user. For instance `(Derivation (Rule (foo A) (= (foo A) (B A))
+    /// (Bindings)))`.
+    pub fn as_atom(&self) -> Atom {
+        let mut children = vec![DERIVATION_SYMBOL];
+        children.extend(self.steps().map(DerivationStep::as_atom));
+        Atom::expr(children)
+    }
+}
+
+impl Display for Derivation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_atom())
+    }
+}
+
+pub const CALL_STACK_SYMBOL : Atom = sym!("CallStack");
+
+#[derive(Clone, PartialEq, Debug)]
+struct CallFrame {
+    atom: Atom,
+    parent: CallStack,
+}
+
+/// Chain of the enclosing expressions which were being reduced when atom
+/// was interpreted. Call stack is collected only when
+/// [InterpreterOptions::call_stack] is set.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CallStack(Option<Rc<CallFrame>>);
+
+impl CallStack {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    fn push(&self, atom: Atom) -> Self {
+        Self(Some(Rc::new(CallFrame{ atom, parent: self.clone() })))
+    }
+
+    /// Returns enclosing expressions starting from the outermost one.
+    pub fn frames(&self) -> Vec<&Atom> {
+        let mut frames = Vec::new();
+        let mut next = &self.0;
+        while let Some(frame) = next {
+            frames.push(&frame.atom);
+            next = &frame.parent.0;
+        }
+        frames.reverse();
+        frames
+    }
+
+    /// Represents the call stack as a MeTTa expression, for instance
+    /// `(CallStack (foo 1) (bar (baz 1)) (baz 1))`.
+    pub fn as_atom(&self) -> Atom {
+        let mut children = vec![CALL_STACK_SYMBOL];
+        children.extend(self.frames().drain(0..).cloned());
+        Atom::expr(children)
+    }
+}
+
+/// Returns `(Error <atom> <reason>)` expression adding call stack as a last
+/// element when it is not empty.
+fn error_atom(atom: Atom, reason: Atom, call_stack: &CallStack) -> Atom {
+    if call_stack.is_empty() {
+        Atom::expr([ERROR_SYMBOL, atom, reason])
+    } else {
+        Atom::expr([ERROR_SYMBOL, atom, reason, call_stack.as_atom()])
+    }
+}
+
 /// Result of atom interpretation plus variable bindings found
 #[derive(Clone, PartialEq)]
-pub struct InterpretedAtom(Atom, Bindings);
+pub struct InterpretedAtom(Atom, Bindings, Derivation, CallStack);
 
 impl InterpretedAtom {
+    fn new(atom: Atom, bindings: Bindings) -> Self {
+        Self(atom, bindings, Derivation::default(), CallStack::default())
+    }
+
+    fn with_call_stack(atom: Atom, bindings: Bindings, call_stack: CallStack) -> Self {
+        Self(atom, bindings, Derivation::default(), call_stack)
+    }
+
     fn atom(&self) -> &Atom {
         &self.0
     }
@@ -93,6 +247,16 @@ impl InterpretedAtom {
     fn bindings(&self) -> &Bindings {
         &self.1
     }
+
+    /// Returns derivation of the result, it is empty when
+    /// [InterpreterOptions::derivation] is not set.
+    pub fn derivation(&self) -> &Derivation {
+        &self.2
+    }
+
+    fn call_stack(&self) -> &CallStack {
+        &self.3
+    }
     
     /// Convert the instance into tuple of [Atom] and [Bindings]
     pub fn into_tuple(self) -> (Atom, Bindings) {
@@ -105,13 +269,6 @@ impl Display for InterpretedAtom {
         if self.1.is_empty() {
             write!(f, "{}", self.0)
         } else {
-            // TODO: it is possible to cleanup all bindings for nested
-            // expressions which were introduced by matching when all
-            // sub-expressions are interpreted. This will simplify
-            // textual representation. For example in test_air_humidity_regulator
-            // (make air wet) leads to (start kettle), {$y: kettle}) result
-            // but $y is not present in the expression after interpreting
-            // (make air wet) and can be removed.
             write!(f, "{}|{}", self.0, self.1)
         }
     }
@@ -124,9 +281,94 @@ impl Debug for InterpretedAtom {
 }
 
 type Results = Vec<InterpretedAtom>;
-type InterpreterError = (Atom, Atom);
+/// Interpreter error: atom which cannot be interpreted, reason of the error
+/// and call stack of the atom.
+pub type InterpreterError = (Atom, Atom, CallStack);
 type NoInputPlan<'a> = Box<dyn Plan<'a, (), Results, InterpreterError> + 'a>;
 
+/// Optional features of the interpreter.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterpreterOptions {
+    /// Collect [Derivation] of each result. Interpreter cache is not used
+    /// when derivation is collected because cached results don't contain
+    /// steps applied.
+    pub derivation: bool,
+    /// Attach [CallStack] to the errors returned. Interpreter cache is not
+    /// used when call stack is collected.
+    pub call_stack: bool,
+    /// Collect reductions and execution time statistics using [Profiler]
+    /// passed.
+    pub profiler: Option<Shared<Profiler>>,
+    /// Stop interpretation with [CANCELLED_SYMBOL] error when token is
+    /// cancelled. Token is checked between plan steps.
+    pub cancellation: Option<CancellationToken>,
+    /// Stop interpretation when this number of results is calculated, more
+    /// results can be returned when they are calculated at once. Interpreter
+    /// cache is not used when number of results is limited.
+    pub max_results: Option<usize>,
+}
+
+/// Token to cancel the interpretation. Token can be cloned and cancelled
+/// from another thread or from a signal handler as cancelling is a single
+/// atomic store.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Request cancellation of the interpretations which use the token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Clear cancellation request, token can be used to run the next
+    /// interpretation after this call.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Plan which checks [CancellationToken] before each step of the wrapped
+/// plan and returns [CANCELLED_SYMBOL] error when the token is cancelled.
+struct CancellablePlan<'a> {
+    token: CancellationToken,
+    atom: Atom,
+    plan: Box<dyn Plan<'a, (), Results, InterpreterError> + 'a>,
+}
+
+impl<'a> Plan<'a, (), Results, InterpreterError> for CancellablePlan<'a> {
+    fn step(self: Box<Self>, _: ()) -> StepResult<'a, Results, InterpreterError> {
+        if self.token.is_cancelled() {
+            log::debug!("CancellablePlan::step: interpretation of {} is cancelled", self.atom);
+            return StepResult::err((self.atom, CANCELLED_SYMBOL, CallStack::default()));
+        }
+        match self.plan.step(()) {
+            StepResult::Execute(plan) => StepResult::execute(CancellablePlan{
+                token: self.token, atom: self.atom, plan }),
+            result => result,
+        }
+    }
+}
+
+impl Debug for CancellablePlan<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.plan.fmt(f)
+    }
+}
+
 /// Initialize interpreter and returns the result of the zero step.
 /// It can be error, immediate result or interpretation plan to be executed.
 /// See [crate::metta::interpreter] for algorithm explanation.
@@ -135,10 +377,45 @@ type NoInputPlan<'a> = Box<dyn Plan<'a, (), Results, InterpreterError> + 'a>;
 /// * `space` - atomspace to query for interpretation
 /// * `expr` - atom to interpret
 pub fn interpret_init<'a, T: Space + 'a>(space: T, expr: &Atom) -> StepResult<'a, Results, InterpreterError> {
-    let context = InterpreterContextRef::new(space);
-    interpret_as_type_plan(context,
-        InterpretedAtom(expr.clone(), Bindings::new()),
-        ATOM_TYPE_UNDEFINED)
+    interpret_init_with_options(space, expr, InterpreterOptions::default())
+}
+
+/// Initialize interpreter using `options` passed. See [interpret_init].
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::*;
+/// use hyperon::common::plan::StepResult;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::interpreter::*;
+///
+/// let space = metta_space("(= (foo $x) (bar $x))");
+/// let options = InterpreterOptions{ derivation: true, ..Default::default() };
+/// let mut step = interpret_init_with_options(&space, &expr!("foo" "A"), options);
+/// while step.has_next() {
+///     step = interpret_step(step);
+/// }
+/// let results = match step {
+///     StepResult::Return(results) => results,
+///     _ => panic!("Result is expected"),
+/// };
+///
+/// assert_eq!(results[0].derivation().to_string(),
+///     "(Derivation (Rule (foo A) (= (foo A) (bar A)) (Bindings)))");
+/// ```
+pub fn interpret_init_with_options<'a, T: Space + 'a>(space: T, expr: &Atom, options: InterpreterOptions) -> StepResult<'a, Results, InterpreterError> {
+    let cancellation = options.cancellation.clone();
+    let max_results = options.max_results;
+    let context = InterpreterContextRef::new(space, options);
+    let step = interpret_as_type_plan(context,
+        InterpretedAtom::new(expr.clone(), Bindings::new()),
+        ATOM_TYPE_UNDEFINED, max_results);
+    match (step, cancellation) {
+        (StepResult::Execute(plan), Some(token)) =>
+            StepResult::execute(CancellablePlan{ token, atom: expr.clone(), plan }),
+        (step, _) => step,
+    }
 }
 
 /// Perform next step of the interpretation plan and return the result. Panics
@@ -163,15 +440,20 @@ pub fn interpret_step<'a>(step: StepResult<'a, Results, InterpreterError>) -> St
 /// * `space` - atomspace to query for interpretation
 /// * `expr` - atom to interpret
 pub fn interpret<T: Space>(space: T, expr: &Atom) -> Result<Vec<Atom>, String> {
-    let mut step = interpret_init(space, expr);
+    interpret_with_options(space, expr, InterpreterOptions::default())
+}
+
+/// Interpret passed atom using `options` passed. See [interpret].
+pub fn interpret_with_options<T: Space>(space: T, expr: &Atom, options: InterpreterOptions) -> Result<Vec<Atom>, String> {
+    let mut step = interpret_init_with_options(space, expr, options);
     while step.has_next() {
         step = interpret_step(step);
     }
     match step {
         StepResult::Return(mut result) => Ok(result.drain(0..)
-            .map(|InterpretedAtom(atom, _)| atom).collect()),
+            .map(|InterpretedAtom(atom, _, _, _)| atom).collect()),
         // TODO: return (Error atom err) expression
-        StepResult::Error((atom, err)) => Ok(vec![Atom::expr([ERROR_SYMBOL, atom, err])]),
+        StepResult::Error((atom, err, call_stack)) => Ok(vec![error_atom(atom, err, &call_stack)]),
         _ => panic!("Not expected step result: {:?}", step),
     }
 }
@@ -192,7 +474,7 @@ impl InterpreterCache {
                 for res in results {
                     let merged = Bindings::merge(res.bindings(), &current_bindings);
                     if let Some(merged) = merged {
-                        result.push(InterpretedAtom(res.atom().clone(), merged));
+                        result.push(InterpretedAtom(res.atom().clone(), merged, res.derivation().clone(), res.call_stack().clone()));
                     } else {
                         inconsistent.push(res);
                     }
@@ -224,6 +506,7 @@ impl SpaceObserver for InterpreterCache {
 }
 
 use std::marker::PhantomData;
+use std::time::Instant;
 
 trait SpaceRef<'a> : Space + 'a {}
 impl<'a, T: Space + 'a> SpaceRef<'a> for T {}
@@ -231,16 +514,65 @@ impl<'a, T: Space + 'a> SpaceRef<'a> for T {}
 struct InterpreterContext<'a, T: SpaceRef<'a>> {
     space: T,
     cache: Rc<RefCell<InterpreterCache>>,
+    options: InterpreterOptions,
     phantom: PhantomData<&'a GroundingSpace>,
 }
 
+impl<'a, T: SpaceRef<'a>> InterpreterContext<'a, T> {
+    fn is_cache_enabled(&self) -> bool {
+        !self.options.derivation && !self.options.call_stack && self.options.max_results.is_none()
+    }
+
+    fn query(&self, expr: &Atom, query: &Atom) -> Vec<Bindings> {
+        match &self.options.profiler {
+            None => self.space.query(query),
+            Some(profiler) => {
+                let start = Instant::now();
+                let results = self.space.query(query);
+                profiler.borrow_mut().add_query(expr, results.len(), start.elapsed());
+                results
+            },
+        }
+    }
+
+    fn execute(&self, expr: &Atom, op: &dyn GroundedAtom, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        match &self.options.profiler {
+            None => op.execute(args),
+            Some(profiler) => {
+                let start = Instant::now();
+                let results = op.execute(args);
+                profiler.borrow_mut().add_execute(expr, start.elapsed());
+                results
+            },
+        }
+    }
+
+    fn push_call(&self, call_stack: &CallStack, atom: &Atom) -> CallStack {
+        if self.options.call_stack {
+            call_stack.push(atom.clone())
+        } else {
+            call_stack.clone()
+        }
+    }
+
+    fn add_derivation_step<F>(&self, derivation: &Derivation, step: F) -> Derivation
+        where F: FnOnce() -> DerivationStep
+    {
+        if self.options.derivation {
+            derivation.with_step(step())
+        } else {
+            derivation.clone()
+        }
+    }
+}
+
 struct InterpreterContextRef<'a, T: SpaceRef<'a>>(Rc<InterpreterContext<'a, T>>);
 
 impl<'a, T: SpaceRef<'a>> InterpreterContextRef<'a, T> {
-    fn new(space: T) -> Self {
+    fn new(space: T, options: InterpreterOptions) -> Self {
         let cache = Rc::new(RefCell::new(InterpreterCache::new()));
         space.register_observer(cache.clone());
-        Self(Rc::new(InterpreterContext{ space, cache, phantom: PhantomData }))
+        Self(Rc::new(InterpreterContext{ space, cache, options, phantom: PhantomData }))
     }
 }
 
@@ -275,8 +607,11 @@ fn has_grounded_sub_expr(expr: &Atom) -> bool {
         });
 }
 
+/// Returns plan to interpret `input` as `typ`. When `max_results` is set the
+/// results of the plan are the results of the whole interpretation, and the
+/// plan stops after this number of results is calculated.
 fn interpret_as_type_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>,
-        input: InterpretedAtom, typ: Atom) -> StepResult<'a, Results, InterpreterError> {
+        input: InterpretedAtom, typ: Atom, max_results: Option<usize>) -> StepResult<'a, Results, InterpreterError> {
     log::debug!("interpret_as_type_plan: input: {}, type: {}", input, typ);
     match input.atom() {
 
@@ -285,6 +620,7 @@ fn interpret_as_type_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a
         Atom::Variable(_) if typ == ATOM_TYPE_VARIABLE => StepResult::ret(vec![input]),
         Atom::Expression(_) if typ == ATOM_TYPE_EXPRESSION => StepResult::ret(vec![input]),
         Atom::Grounded(_) if typ == ATOM_TYPE_GROUNDED => StepResult::ret(vec![input]),
+        _ if atom_is_error(input.atom()) => StepResult::ret(vec![input]),
 
         Atom::Symbol(_) | Atom::Grounded(_) =>
             cast_atom_to_type_plan(context, input, typ),
@@ -296,7 +632,7 @@ fn interpret_as_type_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a
             let op = &expr.children()[0];
             StepResult::execute(SequencePlan::new(
                     get_type_of_atom_plan(context.clone(), op.clone()),
-                    interpret_expression_as_type_plan(context, input, typ)
+                    interpret_expression_as_type_plan(context, input, typ, max_results)
             ))
         },
 
@@ -312,23 +648,32 @@ fn cast_atom_to_type_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a
     let typ = apply_bindings_to_atom(&typ, input.bindings());
     let mut results = get_type_bindings(&context.space, input.atom(), &typ);
     log::debug!("cast_atom_to_type_plan: type check results: {:?}", results);
+    // different types of the atom giving the same bindings lead to the same result
+    let mut i = 0;
+    while i < results.len() {
+        if results[..i].iter().any(|(_, bindings)| *bindings == results[i].1) {
+            results.remove(i);
+        } else {
+            i += 1;
+        }
+    }
     if !results.is_empty() {
         log::debug!("cast_atom_to_type_plan: input: {} is casted to type: {}", input, typ);
         StepResult::ret(results.drain(0..).map(|(_match_typ, typ_bindings)| {
-            let InterpretedAtom(atom, bindings) = input.clone();
+            let InterpretedAtom(atom, bindings, derivation, call_stack) = input.clone();
             // TODO: need to understand if it is needed to apply bindings
             // should we apply bindings to bindings?
             let bindings = Bindings::merge(&bindings, &typ_bindings);
             if let Some(bindings) = bindings {
                 let atom = apply_bindings_to_atom(&atom, &bindings);
-                Some(InterpretedAtom(atom, bindings))
+                Some(InterpretedAtom(atom, bindings, derivation, call_stack))
             } else {
                 None
             }
         }).filter(Option::is_some).map(Option::unwrap).collect())
     } else {
         log::debug!("cast_atom_to_type_plan: input: {} cannot be casted to type: {}", input, typ);
-        StepResult::err((input.0, BAD_TYPE_SYMBOL))
+        StepResult::err((input.0, BAD_TYPE_SYMBOL, input.3))
     }
 }
 
@@ -338,12 +683,15 @@ fn get_type_of_atom_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a,
 }
 
 fn interpret_expression_as_type_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>,
-        input: InterpretedAtom, typ: Atom) -> OperatorPlan<'a, Vec<Atom>, Results, InterpreterError> {
+        input: InterpretedAtom, typ: Atom, max_results: Option<usize>) -> OperatorPlan<'a, Vec<Atom>, Results, InterpreterError> {
     let descr = format!("form alternative plans for expression {} using types", input);
-    OperatorPlan::new(move |op_types: Vec<Atom>| {
-        make_alternives_plan(input.0.clone(), op_types, move |op_typ| {
+    OperatorPlan::new(move |mut op_types: Vec<Atom>| {
+        // all non-functional types of the operation lead to the same plan
+        let mut has_non_func = false;
+        op_types.retain(|typ| is_func(typ) || !std::mem::replace(&mut has_non_func, true));
+        make_alternives_plan(input.0.clone(), input.3.clone(), op_types, max_results, move |op_typ| {
             interpret_expression_as_type_op(context.clone(),
-                input.clone(), op_typ, typ.clone())
+                input.clone(), op_typ, typ.clone(), max_results)
         })
     }, descr)
 }
@@ -363,24 +711,24 @@ fn get_expr_mut(atom: &mut Atom) -> &mut ExpressionAtom {
 }
 
 fn interpret_expression_as_type_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>,
-        input: InterpretedAtom, op_typ: Atom, ret_typ: Atom) -> NoInputPlan<'a> {
+        input: InterpretedAtom, op_typ: Atom, ret_typ: Atom, max_results: Option<usize>) -> NoInputPlan<'a> {
     log::debug!("interpret_expression_as_type_op: input: {}, operation type: {}, expected return type: {}", input, op_typ, ret_typ);
     if ret_typ == ATOM_TYPE_ATOM || ret_typ == ATOM_TYPE_EXPRESSION {
         Box::new(StepResult::ret(vec![input]))
     } else if is_func(&op_typ) {
-        let InterpretedAtom(input_atom, mut input_bindings) = input;
+        let InterpretedAtom(input_atom, mut input_bindings, derivation, call_stack) = input;
         let expr = get_expr(&input_atom);
         let (op_arg_types, op_ret_typ) = get_arg_types(&op_typ);
-        // TODO: supertypes should be checked as well
-        if !match_reducted_types(op_ret_typ, &ret_typ, &mut input_bindings) {
-            Box::new(StepResult::err((input_atom, BAD_TYPE_SYMBOL)))
+        if !match_subtype(&context.space, op_ret_typ, &ret_typ, &mut input_bindings) {
+            Box::new(StepResult::err((input_atom, BAD_TYPE_SYMBOL, call_stack)))
         } else if op_arg_types.len() != (expr.children().len() - 1) {
-            Box::new(StepResult::err((input_atom, INCORRECT_NUMBER_OF_ARGUMENTS_SYMBOL)))
+            Box::new(StepResult::err((input_atom, INCORRECT_NUMBER_OF_ARGUMENTS_SYMBOL, call_stack)))
         } else {
-            let input = InterpretedAtom(input_atom, input_bindings);
+            let input = InterpretedAtom(input_atom, input_bindings, derivation, call_stack);
             let expr = get_expr(input.atom());
             assert!(!expr.children().is_empty(), "Empty expression is not expected");
             let mut plan: NoInputPlan = Box::new(StepResult::ret(vec![input.clone()]));
+            let input_call_stack = context.push_call(input.call_stack(), input.atom());
             for expr_idx in 0..(expr.children().len()) {
                 let arg = expr.children()[expr_idx].clone();
                 let arg_typ = if expr_idx > 0 {
@@ -389,95 +737,145 @@ fn interpret_expression_as_type_op<'a, T: SpaceRef<'a>>(context: InterpreterCont
                     op_typ.clone()
                 };
                 let context = context.clone();
+                let input_call_stack = input_call_stack.clone();
                 plan = Box::new(SequencePlan::new(
                     plan,
                     OperatorPlan::new(move |results: Results| {
-                        make_alternives_plan(arg.clone(), results, move |result| -> NoInputPlan {
+                        let call_stack = input_call_stack.clone();
+                        make_alternives_plan(arg.clone(), call_stack, results, None, move |result| -> NoInputPlan {
                             let arg_typ = apply_bindings_to_atom(&arg_typ, result.bindings());
+                            let call_stack = context.push_call(result.call_stack(), result.atom());
                             Box::new(SequencePlan::new(
                                 interpret_as_type_plan(context.clone(),
-                                    InterpretedAtom(arg.clone(), result.bindings().clone()),
-                                    arg_typ),
-                                insert_reducted_arg_plan(result, expr_idx)))
+                                    InterpretedAtom::with_call_stack(arg.clone(), result.bindings().clone(), call_stack),
+                                    arg_typ, None),
+                                insert_reducted_arg_plan(context.clone(), result, expr_idx)))
                         })
                     }, format!("Interpret {} argument", expr_idx))
                 ))
             }
-            call_alternatives_plan(plan, context, input)
+            call_alternatives_plan(plan, context, input, true, max_results)
         }
     } else {
         let expr = get_expr(input.atom());
         let mut plan: NoInputPlan = Box::new(StepResult::ret(vec![input.clone()]));
+        let input_call_stack = context.push_call(input.call_stack(), input.atom());
         for expr_idx in 0..(expr.children().len()) {
             let arg = expr.children()[expr_idx].clone();
             let context = context.clone();
+            let input_call_stack = input_call_stack.clone();
             plan = Box::new(SequencePlan::new(
                 plan,
                 OperatorPlan::new(move |results: Results| {
-                    make_alternives_plan(arg.clone(), results, move |result| -> NoInputPlan {
+                    let call_stack = input_call_stack.clone();
+                    make_alternives_plan(arg.clone(), call_stack, results, None, move |result| -> NoInputPlan {
+                        let call_stack = context.push_call(result.call_stack(), result.atom());
                         Box::new(SequencePlan::new(
                             interpret_as_type_plan(context.clone(),
-                                InterpretedAtom(arg.clone(), result.bindings().clone()),
-                                ATOM_TYPE_UNDEFINED),
-                            insert_reducted_arg_plan(result, expr_idx)))
+                                InterpretedAtom::with_call_stack(arg.clone(), result.bindings().clone(), call_stack),
+                                ATOM_TYPE_UNDEFINED, None),
+                            insert_reducted_arg_plan(context.clone(), result, expr_idx)))
                     })
                 }, format!("Interpret {} argument", expr_idx))
             ))
         }
-        call_alternatives_plan(plan, context, input)
+        call_alternatives_plan(plan, context, input, false, max_results)
     }
 }
 
+/// Calls each alternative of the reducted expression. When
+/// `propagate_errors` is set then an error returned by an argument is
+/// returned instead of calling the expression.
 fn call_alternatives_plan<'a, T: SpaceRef<'a>>(plan: NoInputPlan<'a>, context: InterpreterContextRef<'a, T>,
-    input: InterpretedAtom) -> NoInputPlan<'a> {
+    input: InterpretedAtom, propagate_errors: bool, max_results: Option<usize>) -> NoInputPlan<'a> {
+    let original = input.atom().clone();
     Box::new(SequencePlan::new(plan, OperatorPlan::new(move |results: Results| {
-        make_alternives_plan(input.0, results, move |result| {
-            call_plan(context.clone(), result)
+        make_alternives_plan(input.0, input.3, results, max_results, move |result| -> NoInputPlan {
+            let error = match propagate_errors {
+                true => reducted_arg_error(&original, result.atom()),
+                false => None,
+            };
+            match error {
+                Some(error) => {
+                    let InterpretedAtom(_, bindings, derivation, call_stack) = result;
+                    Box::new(StepResult::ret(vec![InterpretedAtom(error, bindings, derivation, call_stack)]))
+                },
+                None => call_plan(context.clone(), result, max_results),
+            }
         })
     }, "interpret each alternative")))
 }
 
-fn insert_reducted_arg_plan<'a>(expr: InterpretedAtom, atom_idx: usize) -> OperatorPlan<'a, Results, Results, InterpreterError> {
+/// Returns the first member of the `reducted` expression which was
+/// interpreted into `(Error ...)` expression. Members which are errors in
+/// the `original` expression already are passed to the operation as is.
+fn reducted_arg_error(original: &Atom, reducted: &Atom) -> Option<Atom> {
+    match (original, reducted) {
+        (Atom::Expression(original), Atom::Expression(reducted)) => original.children().iter()
+            .zip(reducted.children().iter())
+            .find(|(original, reducted)| atom_is_error(reducted) && original != reducted)
+            .map(|(_, reducted)| reducted.clone()),
+        _ => None,
+    }
+}
+
+fn insert_reducted_arg_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, expr: InterpretedAtom, atom_idx: usize) -> OperatorPlan<'a, Results, Results, InterpreterError> {
     let descr = format!("insert right element as child {} of left element", atom_idx);
-    OperatorPlan::new(move |arg_variants| insert_reducted_arg_op(expr, atom_idx, arg_variants), descr)
+    OperatorPlan::new(move |arg_variants| insert_reducted_arg_op(context, expr, atom_idx, arg_variants), descr)
 }
 
-fn insert_reducted_arg_op<'a>(expr: InterpretedAtom, atom_idx: usize, mut arg_variants: Results) -> StepResult<'a, Results, InterpreterError> {
+fn insert_reducted_arg_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, expr: InterpretedAtom, atom_idx: usize, mut arg_variants: Results) -> StepResult<'a, Results, InterpreterError> {
     let result = arg_variants.drain(0..).map(|arg| {
-        let InterpretedAtom(arg, bindings) = arg;
+        let InterpretedAtom(arg, bindings, arg_derivation, _) = arg;
         let mut expr_with_arg = expr.atom().clone();
         get_expr_mut(&mut expr_with_arg).children_mut()[atom_idx] = arg;
-        InterpretedAtom(apply_bindings_to_atom(&expr_with_arg, &bindings), bindings)
+        let derivation = if arg_derivation.is_empty() {
+            expr.derivation().clone()
+        } else {
+            context.add_derivation_step(expr.derivation(),
+                || DerivationStep::Argument{ index: atom_idx, derivation: arg_derivation })
+        };
+        InterpretedAtom(apply_bindings_to_atom(&expr_with_arg, &bindings), bindings,
+            derivation, expr.call_stack().clone())
     }).collect();
     log::debug!("insert_reducted_arg_op: result: {:?}", result);
     StepResult::ret(result)
 }
 
-fn call_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom) -> NoInputPlan<'a> {
+fn call_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom, max_results: Option<usize>) -> NoInputPlan<'a> {
     let descr = format!("call {}", input);
-    Box::new(OperatorPlan::new(|_| call_op(context, input), descr))
+    Box::new(OperatorPlan::new(move |_| call_op(context, input, max_results), descr))
 }
 
-fn call_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom) -> StepResult<'a, Results, InterpreterError> {
+fn call_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom, max_results: Option<usize>) -> StepResult<'a, Results, InterpreterError> {
     log::debug!("call_op: {}", input);
 
-    let cached = context.cache.borrow().get(input.atom(), input.bindings());
+    let cached = if context.is_cache_enabled() {
+        context.cache.borrow().get(input.atom(), input.bindings())
+    } else {
+        None
+    };
     if let Some(result) = cached {
         return_cached_result_plan(result)
     } else {
         if let Atom::Expression(_) = input.atom() {
-            if !has_grounded_sub_expr(input.atom()) {
+            let narrow = narrow_bindings_plan(&input);
+            if context.is_cache_enabled() && !has_grounded_sub_expr(input.atom()) {
                 let key = input.atom().clone();
                 StepResult::execute(SequencePlan::new(
-                    OrPlan::new(
-                        interpret_reducted_plan(context.clone(), input.clone()),
-                        StepResult::ret(vec![input])),
+                    SequencePlan::new(
+                        OrPlan::new(
+                            interpret_reducted_plan(context.clone(), input.clone(), max_results),
+                            StepResult::ret(vec![input])),
+                        narrow),
                     save_result_in_cache_plan(context, key)
                 ))
             } else {
-                StepResult::execute(OrPlan::new(
-                        interpret_reducted_plan(context.clone(), input.clone()),
-                        StepResult::ret(vec![input])))
+                StepResult::execute(SequencePlan::new(
+                    OrPlan::new(
+                        interpret_reducted_plan(context.clone(), input.clone(), max_results),
+                        StepResult::ret(vec![input])),
+                    narrow))
             }
         } else {
             panic!("Only expressions are expected to be called");
@@ -485,6 +883,33 @@ fn call_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: In
     }
 }
 
+/// Removes bindings of the variables which were introduced while `input` was
+/// interpreted and which are not visible outside of the sub-expression.
+/// Variables of the `input` atom, variables of the `input` bindings and
+/// variables of the result atom are kept, thus bindings which can be used by
+/// enclosing expressions are not lost.
+fn narrow_bindings_plan<'a>(input: &InterpretedAtom) -> OperatorPlan<'a, Results, Results, InterpreterError> {
+    let descr = format!("narrow bindings of {} results", input);
+    let mut vars = HashSet::new();
+    input.atom().iter().filter_map(AtomIter::extract_var)
+        .for_each(|var| { vars.insert(var.clone()); });
+    input.bindings().iter().for_each(|(var, value)| {
+        vars.insert(var.clone());
+        value.iter().filter_map(AtomIter::extract_var)
+            .for_each(|var| { vars.insert(var.clone()); });
+    });
+    OperatorPlan::new(move |mut results: Results| {
+        let results = results.drain(0..).map(|InterpretedAtom(atom, bindings, derivation, call_stack)| {
+            let mut vars = vars.clone();
+            atom.iter().filter_map(AtomIter::extract_var)
+                .for_each(|var| { vars.insert(var.clone()); });
+            let bindings = bindings.narrow_vars(&vars);
+            InterpretedAtom(atom, bindings, derivation, call_stack)
+        }).collect();
+        StepResult::ret(results)
+    }, descr)
+}
+
 fn return_cached_result_plan<'a>(results: Results) -> StepResult<'a, Results, InterpreterError> {
     let descr = format!("return cached results {:?}", results);
     StepResult::execute(OperatorPlan::new(|_| StepResult::ret(results), descr))
@@ -499,12 +924,12 @@ fn save_result_in_cache_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef
 }
 
 fn interpret_reducted_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>,
-        input: InterpretedAtom) -> NoInputPlan<'a> {
+        input: InterpretedAtom, max_results: Option<usize>) -> NoInputPlan<'a> {
     if let Atom::Expression(ref expr) = input.atom() {
         if is_grounded_op(expr) {
-            Box::new(execute_plan(context, input))
+            Box::new(execute_plan(context, input, max_results))
         } else {
-            Box::new(match_plan(context, input))
+            Box::new(match_plan(context, input, max_results))
         }
     } else {
         panic!("Only expression is expected, received: {}", input);
@@ -512,36 +937,47 @@ fn interpret_reducted_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'
 }
 
 
-fn execute_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom) -> OperatorPlan<'a, (), Results, InterpreterError> {
+fn execute_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom, max_results: Option<usize>) -> OperatorPlan<'a, (), Results, InterpreterError> {
     let descr = format!("execute {}", input);
-    OperatorPlan::new(|_| execute_op(context, input), descr)
+    OperatorPlan::new(move |_| execute_op(context, input, max_results), descr)
 }
 
-fn execute_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom) -> StepResult<'a, Results, InterpreterError> {
+fn execute_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom, max_results: Option<usize>) -> StepResult<'a, Results, InterpreterError> {
     log::debug!("execute_op: {}", input);
     match input {
-        InterpretedAtom(Atom::Expression(ref expr), ref bindings) => {
+        InterpretedAtom(Atom::Expression(ref expr), ref bindings, ref derivation, ref call_stack) => {
             let mut expr = expr.clone();
             let op = expr.children().get(0).cloned();
             if let Some(Atom::Grounded(op)) = op {
                 let mut args = expr.children_mut().drain(1..).collect();
-                match op.execute(&mut args) {
+                let result_call_stack = context.push_call(call_stack, input.atom());
+                let grounded_step = |result: &Atom| {
+                    context.add_derivation_step(derivation, || DerivationStep::Grounded{
+                        expr: input.atom().clone(), result: result.clone() })
+                };
+                match context.execute(input.atom(), op.as_ref(), &mut args) {
                     Ok(mut vec) => {
                         let results: Vec<InterpretedAtom> = vec.drain(0..)
-                            .map(|atom| InterpretedAtom(atom, bindings.clone()))
+                            .map(|atom| {
+                                let derivation = grounded_step(&atom);
+                                InterpretedAtom(atom, bindings.clone(), derivation, result_call_stack.clone())
+                            })
                             .collect();
                         if results.is_empty() {
                             StepResult::ret(results)
                         } else {
-                            make_alternives_plan(input.0, results, move |result| {
+                            make_alternives_plan(input.0, input.3, results, max_results, move |result| {
                                 interpret_as_type_plan(context.clone(),
-                                    result, ATOM_TYPE_UNDEFINED)
+                                    result, ATOM_TYPE_UNDEFINED, max_results)
                             })
                         }
                     },
-                    Err(ExecError::Runtime(msg)) => StepResult::ret(vec![InterpretedAtom(
-                           Atom::expr([ERROR_SYMBOL, input.0, Atom::sym(msg)]), input.1)]),
-                    Err(ExecError::NoReduce) => StepResult::err((input.0, NOT_REDUCIBLE_SYMBOL)),
+                    Err(ExecError::Runtime(msg)) => {
+                        let error = error_atom(input.0.clone(), Atom::sym(msg), call_stack);
+                        let derivation = grounded_step(&error);
+                        StepResult::ret(vec![InterpretedAtom(error, input.1, derivation, input.3)])
+                    },
+                    Err(ExecError::NoReduce) => StepResult::err((input.0, NOT_REDUCIBLE_SYMBOL, input.3)),
                 }
             } else {
                 panic!("Trying to execute non grounded atom: {}", expr)
@@ -551,16 +987,17 @@ fn execute_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input:
     }
 }
 
-fn match_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom) -> OperatorPlan<'a, (), Results, InterpreterError> {
+fn match_plan<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom, max_results: Option<usize>) -> OperatorPlan<'a, (), Results, InterpreterError> {
     let descr = format!("match {}", input);
-    OperatorPlan::new(|_| match_op(context, input), descr)
+    OperatorPlan::new(move |_| match_op(context, input, max_results), descr)
 }
 
-fn match_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom) -> StepResult<'a, Results, InterpreterError> {
+fn match_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: InterpretedAtom, max_results: Option<usize>) -> StepResult<'a, Results, InterpreterError> {
     log::debug!("match_op: {}", input);
     let var_x = VariableAtom::new("X").make_unique();
     let query = Atom::expr(vec![EQUAL_SYMBOL, input.atom().clone(), Atom::Variable(var_x.clone())]);
-    let mut query_bindings = context.space.query(&query);
+    let mut query_bindings = context.query(input.atom(), &query);
+    let result_call_stack = context.push_call(input.call_stack(), input.atom());
     let results: Vec<InterpretedAtom> = query_bindings
         .drain(0..)
         .map(|mut query_binding| {
@@ -574,46 +1011,58 @@ fn match_op<'a, T: SpaceRef<'a>>(context: InterpreterContextRef<'a, T>, input: I
                 Bindings::merge(&query_binding, &bindings).ok_or(())
             });
             log::debug!("match_op: query: {}, bindings: {:?}, result: {}", input, bindings, result);
-            (result, bindings)
+            let derivation = context.add_derivation_step(input.derivation(), || {
+                let expr = apply_bindings_to_atom(input.atom(), &query_binding);
+                let rule = Atom::expr([EQUAL_SYMBOL, expr, result.clone()]);
+                let mut input_vars = HashSet::new();
+                input.atom().iter().filter_map(AtomIter::extract_var)
+                    .for_each(|var| { input_vars.insert(var.clone()); });
+                let bindings = query_binding.narrow_vars(&input_vars);
+                DerivationStep::Rule{ expr: input.atom().clone(), rule, bindings }
+            });
+            (result, bindings, derivation)
         })
-        .filter(|(_, bindings)| bindings.is_ok())
-        .map(|(result, bindings)| InterpretedAtom(result, bindings.unwrap()))
+        .filter(|(_, bindings, _)| bindings.is_ok())
+        .map(|(result, bindings, derivation)| InterpretedAtom(result, bindings.unwrap(),
+            derivation, result_call_stack.clone()))
         .collect();
-    make_alternives_plan(input.0, results, move |result| {
-        interpret_as_type_plan(context.clone(), result, ATOM_TYPE_UNDEFINED)
+    make_alternives_plan(input.0, input.3, results, max_results, move |result| {
+        interpret_as_type_plan(context.clone(), result, ATOM_TYPE_UNDEFINED, max_results)
     })
 }
 
-fn make_alternives_plan<'a, T: Debug, F, P>(input: Atom, mut results: Vec<T>,
-    plan: F) -> StepResult<'a, Results, InterpreterError>
+fn make_alternives_plan<'a, T: Debug, F, P>(input: Atom, call_stack: CallStack, mut results: Vec<T>,
+    max_results: Option<usize>, plan: F) -> StepResult<'a, Results, InterpreterError>
 where
     F: 'a + Fn(T) -> P,
     P: 'a + Plan<'a, (), Results, InterpreterError>
 {
     log::debug!("make_alternives_plan: input: {:?}, alternatives: {:?}", input, results);
     match results.len() {
-        0 => StepResult::err((input, NO_VALID_ALTERNATIVES)),
+        0 => StepResult::err((input, NO_VALID_ALTERNATIVES, call_stack)),
         1 => StepResult::execute(plan(results.pop().unwrap())),
         _ => {
             StepResult::execute(AlternativeInterpretationsPlan::new(
                 input,
                 results.drain(0..)
                     .map(|result| -> NoInputPlan { Box::new(plan(result)) })
-                    .collect()))
+                    .collect()).with_call_stack(call_stack).with_max_results(max_results))
         },
     }
 }
 
-use std::collections::VecDeque;
+use std::collections::{VecDeque, HashSet};
 
 /// Plan which interprets in parallel alternatives of the expression.
 /// Each successful result is appended to the overall result of the plan.
 /// If no alternatives returned successful result the plan returns error. 
 pub struct AlternativeInterpretationsPlan<'a, T> {
     atom: Atom,
+    call_stack: CallStack,
     plans: VecDeque<Box<dyn Plan<'a, (), Vec<T>, InterpreterError> + 'a>>,
     results: Vec<T>,
     success: bool,
+    max_results: Option<usize>,
 }
 
 impl<'a, T> AlternativeInterpretationsPlan<'a, T> {
@@ -623,18 +1072,33 @@ impl<'a, T> AlternativeInterpretationsPlan<'a, T> {
     /// `atom` - atom to be printed as root of the alternative interpretations
     /// `plan` - altenative plans for the atom
     pub fn new(atom: Atom, plans: Vec<Box<dyn Plan<'a, (), Vec<T>, InterpreterError> + 'a>>) -> Self {
-        Self{ atom, plans: plans.into(), results: Vec::new(), success: false }
+        Self{ atom, call_stack: CallStack::default(), plans: plans.into(), results: Vec::new(), success: false, max_results: None }
+    }
+
+    /// Set call stack of the atom to be returned with the error when all
+    /// alternatives are failed.
+    pub fn with_call_stack(mut self, call_stack: CallStack) -> Self {
+        self.call_stack = call_stack;
+        self
+    }
+
+    /// Set number of results after which the rest of the alternatives are
+    /// not interpreted, `None` means all alternatives are interpreted.
+    pub fn with_max_results(mut self, max_results: Option<usize>) -> Self {
+        self.max_results = max_results;
+        self
     }
 }
 
 impl<'a, T: Debug> Plan<'a, (), Vec<T>, InterpreterError> for AlternativeInterpretationsPlan<'a, T> {
     fn step(mut self: Box<Self>, _: ()) -> StepResult<'a, Vec<T>, InterpreterError> {
         log::debug!("AlternativeInterpretationsPlan::step: {} alternatives left", self.plans.len());
-        if self.plans.len() == 0 {
+        let enough = self.success && matches!(self.max_results, Some(max) if self.results.len() >= max);
+        if self.plans.len() == 0 || enough {
             if self.success {
                 StepResult::ret(self.results)
             } else {
-                StepResult::err((self.atom, NO_VALID_ALTERNATIVES))
+                StepResult::err((self.atom, NO_VALID_ALTERNATIVES, self.call_stack))
             }
         } else {
             let plan = self.plans.pop_front().unwrap();
@@ -748,12 +1212,12 @@ mod tests {
 
     #[test]
     fn test_make_alternatives_plan_no_alternative() {
-        let plan = make_alternives_plan(sym!("Test"),
-            vec![], |_res: InterpretedAtom| StepResult::ret(vec![]));
+        let plan = make_alternives_plan(sym!("Test"), CallStack::default(),
+            vec![], None, |_res: InterpretedAtom| StepResult::ret(vec![]));
 
         let result = test_interpret(plan, ());
 
-        assert_eq!(Err((sym!("Test"), NO_VALID_ALTERNATIVES)), result);
+        assert_eq!(Err((sym!("Test"), NO_VALID_ALTERNATIVES, CallStack::default())), result);
     }
 
     #[test]
@@ -780,7 +1244,7 @@ mod tests {
     #[test]
     fn test_alternatives_plan_error_present() {
         let plan = AlternativeInterpretationsPlan::new(sym!("Test"),
-            vec![Box::new(StepResult::err((sym!("Test"), BAD_TYPE_SYMBOL))),
+            vec![Box::new(StepResult::err((sym!("Test"), BAD_TYPE_SYMBOL, CallStack::default()))),
                 Box::new(StepResult::ret(vec!["C", "D"]))]);
 
         let result = test_interpret(plan, ());
@@ -792,12 +1256,12 @@ mod tests {
     fn test_alternatives_plan_only_errors() {
         let plan: AlternativeInterpretationsPlan<&'static str> =
             AlternativeInterpretationsPlan::new(sym!("Test"),
-            vec![Box::new(StepResult::err((sym!("Test"), sym!("Expected error")))),
-                Box::new(StepResult::err((sym!("Test"), sym!("Another expected error"))))]);
+            vec![Box::new(StepResult::err((sym!("Test"), sym!("Expected error"), CallStack::default()))),
+                Box::new(StepResult::err((sym!("Test"), sym!("Another expected error"), CallStack::default())))]);
 
         let result = test_interpret(plan, ());
 
-        assert_eq!(Err((sym!("Test"), NO_VALID_ALTERNATIVES)), result);
+        assert_eq!(Err((sym!("Test"), NO_VALID_ALTERNATIVES, CallStack::default())), result);
     }
 
     #[test]
@@ -864,6 +1328,34 @@ mod tests {
             Ok(vec![Atom::expr([ERROR_SYMBOL, expr, Atom::sym("Runtime test error")])]));
     }
 
+    #[test]
+    fn return_runtime_error_with_call_stack() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!("=" ("foo" x) ("bar" x)));
+        space.add(expr!("=" ("bar" x) ({ThrowError()} x)));
+        let expr = expr!("foo" {"deep error"});
+        let options = InterpreterOptions{ call_stack: true, ..Default::default() };
+
+        assert_eq!(interpret_with_options(&space, &expr, options),
+            Ok(vec![expr!("Error" ({ThrowError()} {"deep error"}) "deep error"
+                ("CallStack" ("foo" {"deep error"}) ("bar" {"deep error"})))]));
+    }
+
+    #[test]
+    fn interpret_propagates_error_of_function_argument() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!(":" "f" ("->" t "B")));
+        let error_call = expr!({ThrowError()} {"arg error"});
+        let error = Atom::expr([ERROR_SYMBOL, error_call.clone(), Atom::sym("arg error")]);
+
+        assert_eq!(interpret(&space, &Atom::expr([sym!("f"), Atom::expr([sym!("f"), error_call.clone()])])),
+            Ok(vec![error.clone()]));
+        assert_eq!(interpret(&space, &Atom::expr([sym!("g"), error_call])),
+            Ok(vec![Atom::expr([sym!("g"), error.clone()])]));
+        assert_eq!(interpret(&space, &Atom::expr([sym!("f"), error.clone()])),
+            Ok(vec![Atom::expr([sym!("f"), error])]));
+    }
+
     #[derive(PartialEq, Clone, Debug)]
     struct NonReducible();
 
@@ -961,6 +1453,140 @@ mod tests {
             Ok(vec![Atom::expr([ERROR_SYMBOL, sym!("myAtom"), BAD_TYPE_SYMBOL])]));
     }
 
+    fn interpret_with_derivation(space: &GroundingSpace, expr: &Atom) -> Vec<(Atom, Atom)> {
+        let options = InterpreterOptions{ derivation: true, ..Default::default() };
+        let mut step = interpret_init_with_options(space, expr, options);
+        while step.has_next() {
+            step = interpret_step(step);
+        }
+        match step {
+            StepResult::Return(results) => results.iter()
+                .map(|res| (res.atom().clone(), res.derivation().as_atom()))
+                .collect(),
+            _ => panic!("Results are expected"),
+        }
+    }
+
+    #[test]
+    fn interpret_collects_derivation() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!("=" ("foo" x) ("bar" x)));
+        space.add(expr!("=" ("bar" x) x));
+        let expr = expr!("foo" ({MulXUndefinedType(3)} {2}));
+
+        assert_eq!(interpret_with_derivation(&space, &expr), vec![(Atom::value(6),
+            expr!("Derivation"
+                ("Argument" "1" ("Derivation" ("Grounded" ({MulXUndefinedType(3)} {2}) {6})))
+                ("Rule" ("foo" {6}) ("=" ("foo" {6}) ("bar" {6})) ("Bindings"))
+                ("Rule" ("bar" {6}) ("=" ("bar" {6}) {6}) ("Bindings"))))]);
+    }
+
+    #[test]
+    fn interpret_collects_rule_bindings() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!("=" ("color") "blue"));
+        space.add(expr!("=" ("eq" x x) "True"));
+        let expr = expr!("eq" y ("color"));
+
+        assert_eq!(interpret_with_derivation(&space, &expr), vec![(sym!("True"),
+            expr!("Derivation"
+                ("Argument" "2" ("Derivation" ("Rule" ("color") ("=" ("color") "blue") ("Bindings"))))
+                ("Rule" ("eq" y "blue") ("=" ("eq" "blue" "blue") "True") ("Bindings" (y "blue")))))]);
+    }
+
+    #[test]
+    fn return_bad_type_error_with_call_stack() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!(":" "myAtom" "myType"));
+        space.add(expr!(":" "id_a" ("->" "A" "A")));
+        space.add(expr!("=" ("id_a" a) a));
+        let options = InterpreterOptions{ call_stack: true, ..Default::default() };
+
+        assert_eq!(interpret_with_options(&space, &expr!("bar" ("id_a" "myAtom")), options),
+            Ok(vec![expr!("Error" "myAtom" "BadType"
+                ("CallStack" ("bar" ("id_a" "myAtom")) ("id_a" "myAtom")))]));
+    }
+
+    #[test]
+    fn interpret_cancelled() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!("=" ("loop") ("loop")));
+        let token = CancellationToken::new();
+        let options = InterpreterOptions{ cancellation: Some(token.clone()), ..Default::default() };
+
+        let mut step = interpret_init_with_options(&space, &expr!(("loop")), options);
+        for _ in 0..10 {
+            step = interpret_step(step);
+        }
+        assert!(step.has_next());
+        token.cancel();
+        let step = interpret_step(step);
+
+        match step {
+            StepResult::Error((atom, err, _)) => {
+                assert_eq!(atom, expr!(("loop")));
+                assert_eq!(err, CANCELLED_SYMBOL);
+            },
+            _ => panic!("Cancellation error is expected"),
+        }
+    }
+
+    #[test]
+    fn interpret_cancelled_from_another_thread() {
+        let mut space = GroundingSpace::new();
+        space.add(expr!("=" ("loop") ("loop")));
+        let token = CancellationToken::new();
+        let options = InterpreterOptions{ cancellation: Some(token.clone()), ..Default::default() };
+
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            token.cancel();
+        });
+        let result = interpret_with_options(&space, &expr!(("loop")), options);
+        canceller.join().unwrap();
+
+        assert_eq!(result, Ok(vec![expr!("Error" ("loop") "Cancelled")]));
+    }
+
+    static ALTERNATIVES: &Operation = &Operation{
+        name: "alternatives",
+        execute: |_, args| Ok(args.clone()),
+        typ: "(-> Atom Atom Atom Atom)",
+    };
+
+    #[test]
+    fn interpret_stops_after_max_results() {
+        let mut space = metta_space("
+            (= (loop) (loop))
+            (= (bar $x) $x)
+        ");
+        space.add(expr!("=" ("foo") ({ALTERNATIVES} "a" ("bar" "b") ("loop"))));
+        let options = |max_results| InterpreterOptions{ max_results: Some(max_results), ..Default::default() };
+
+        assert_eq!(interpret_with_options(&space, &expr!(("foo")), options(1)), Ok(vec![sym!("a")]));
+        assert_eq!(interpret_with_options(&space, &expr!(("foo")), options(2)), Ok(vec![sym!("a"), sym!("b")]));
+    }
+
+    #[test]
+    fn interpret_narrows_result_bindings() {
+        let space = metta_space("
+            (= (ift T $then) $then)
+            (= (make $x) (ift (makes $y $x) (start $y)))
+            (= (makes humidifier (air wet)) T)
+            (= (makes kettle (air wet)) T)
+        ");
+        let mut step = interpret_init(&space, &metta_atom("(make (air wet))"));
+        while step.has_next() {
+            step = interpret_step(step);
+        }
+        let results: Vec<String> = match step {
+            StepResult::Return(results) => results.iter().map(|res| res.to_string()).collect(),
+            _ => panic!("Results are expected"),
+        };
+
+        assert_eq_no_order!(results, vec!["(start humidifier)".to_string(), "(start kettle)".to_string()]);
+    }
+
     #[test]
     fn operation_is_expression() {
         let mut space = GroundingSpace::new();
@@ -971,5 +1597,31 @@ mod tests {
 
         assert_eq!(interpret(&space, &expr!(("foo") "a")), Ok(vec![expr!("a")]));
     }
-}
 
+    #[test]
+    fn interpret_checks_result_super_types() {
+        let space = metta_space("
+            (:< Cat Animal)
+            (:< (List $t) (Seq $t))
+            (: tom Cat)
+            (: get-cat (-> Cat))
+            (= (get-cat) tom)
+            (: pet (-> Animal Animal))
+            (= (pet $x) $x)
+            (: cats (-> (List Cat)))
+            (= (cats) (tom))
+            (: head (-> (Seq $t) $t))
+            (= (head ($x)) $x)
+            (: get-animal (-> Animal))
+            (= (get-animal) tom)
+            (: feed (-> Cat Cat))
+            (= (feed $x) $x)
+        ");
+
+        assert_eq!(interpret(&space, &metta_atom("(pet tom)")), Ok(vec![expr!("tom")]));
+        assert_eq!(interpret(&space, &metta_atom("(pet (get-cat))")), Ok(vec![expr!("tom")]));
+        assert_eq!(interpret(&space, &metta_atom("(head (cats))")), Ok(vec![expr!("tom")]));
+        assert_eq!(interpret(&space, &metta_atom("(feed (get-animal))")),
+            Ok(vec![expr!("Error" ("get-animal") "BadType")]));
+    }
+}
@@ -4,6 +4,9 @@ pub mod text;
 pub mod interpreter;
 pub mod types;
 pub mod runner;
+pub mod profiler;
+pub mod inference;
+pub mod type_index;
 
 use text::{SExprParser, Tokenizer};
 use regex::Regex;
@@ -29,6 +32,15 @@ pub const BAD_TYPE_SYMBOL : Atom = sym!("BadType");
 pub const INCORRECT_NUMBER_OF_ARGUMENTS_SYMBOL : Atom = sym!("IncorrectNumberOfArguments");
 pub const NOT_REDUCIBLE_SYMBOL : Atom = sym!("NotReducible");
 pub const NO_VALID_ALTERNATIVES : Atom = sym!("NoValidAlternatives");
+pub const CANCELLED_SYMBOL : Atom = sym!("Cancelled");
+
+/// Returns true when atom is an `(Error ...)` expression.
+pub fn atom_is_error(atom: &Atom) -> bool {
+    match atom {
+        Atom::Expression(expr) => expr.children().first() == Some(&ERROR_SYMBOL),
+        _ => false,
+    }
+}
 
 pub fn metta_space(text: &str) -> GroundingSpace {
     let tokenizer = common_tokenizer();
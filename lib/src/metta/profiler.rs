@@ -0,0 +1,151 @@
+//! Profiler collects statistics of the MeTTa program interpretation. It is
+//! enabled by passing [Profiler] instance via
+//! [crate::metta::interpreter::InterpreterOptions::profiler]. Statistics is
+//! collected per head symbol of the expressions reduced using `=` rules and
+//! per grounded operation executed.
+
+use crate::*;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub const PROFILE_SYMBOL : Atom = sym!("Profile");
+pub const FUNCTIONS_SYMBOL : Atom = sym!("Functions");
+pub const GROUNDED_OPS_SYMBOL : Atom = sym!("GroundedOps");
+
+/// Statistics of the function defined via `=` rules.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FunctionProfile {
+    /// Number of `=` queries made to reduce the function calls
+    pub queries: usize,
+    /// Number of reductions found by queries
+    pub reductions: usize,
+    /// Overall time spent in `=` queries
+    pub query_time: Duration,
+}
+
+/// Statistics of the grounded operation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GroundedProfile {
+    /// Number of operation calls
+    pub calls: usize,
+    /// Overall time spent in [Grounded::execute] of the operation
+    pub execute_time: Duration,
+}
+
+/// Interpretation statistics collector.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    functions: HashMap<String, FunctionProfile>,
+    grounded_ops: HashMap<String, GroundedProfile>,
+}
+
+fn head_name(expr: &Atom) -> String {
+    match expr {
+        Atom::Expression(expr) => expr.children().first()
+            .map_or_else(|| "()".into(), |head| head.to_string()),
+        _ => expr.to_string(),
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Clear all collected statistics.
+    pub fn reset(&mut self) {
+        self.functions.clear();
+        self.grounded_ops.clear();
+    }
+
+    /// Record `=` query made to reduce the `expr`. `reductions` is a number
+    /// of results returned by query.
+    pub fn add_query(&mut self, expr: &Atom, reductions: usize, time: Duration) {
+        let profile = self.functions.entry(head_name(expr)).or_default();
+        profile.queries += 1;
+        profile.reductions += reductions;
+        profile.query_time += time;
+    }
+
+    /// Record execution of the grounded operation which is a head of the
+    /// `expr`.
+    pub fn add_execute(&mut self, expr: &Atom, time: Duration) {
+        let profile = self.grounded_ops.entry(head_name(expr)).or_default();
+        profile.calls += 1;
+        profile.execute_time += time;
+    }
+
+    /// Returns statistics of the functions sorted by query time descending.
+    pub fn functions(&self) -> Vec<(&String, &FunctionProfile)> {
+        let mut functions: Vec<(&String, &FunctionProfile)> = self.functions.iter().collect();
+        functions.sort_by(|(a_name, a), (b_name, b)|
+            b.query_time.cmp(&a.query_time).then(a_name.cmp(b_name)));
+        functions
+    }
+
+    /// Returns statistics of the grounded operations sorted by execute time
+    /// descending.
+    pub fn grounded_ops(&self) -> Vec<(&String, &GroundedProfile)> {
+        let mut ops: Vec<(&String, &GroundedProfile)> = self.grounded_ops.iter().collect();
+        ops.sort_by(|(a_name, a), (b_name, b)|
+            b.execute_time.cmp(&a.execute_time).then(a_name.cmp(b_name)));
+        ops
+    }
+
+    /// Represents the report as a MeTTa expression. Time is represented in
+    /// microseconds. For example:
+    /// ```text
+    /// (Profile
+    ///   (Functions (fib (Queries 15) (Reductions 15) (QueryTimeUs 120)))
+    ///   (GroundedOps (+ (Calls 7) (ExecuteTimeUs 3))))
+    /// ```
+    pub fn as_atom(&self) -> Atom {
+        let count = |name: &str, value: usize| Atom::expr([Atom::sym(name), Atom::sym(value.to_string())]);
+        let time = |name: &str, value: &Duration| Atom::expr([Atom::sym(name), Atom::sym(value.as_micros().to_string())]);
+        let mut functions = vec![FUNCTIONS_SYMBOL];
+        functions.extend(self.functions().drain(0..).map(|(name, profile)| Atom::expr([
+            Atom::sym(name),
+            count("Queries", profile.queries),
+            count("Reductions", profile.reductions),
+            time("QueryTimeUs", &profile.query_time),
+        ])));
+        let mut grounded_ops = vec![GROUNDED_OPS_SYMBOL];
+        grounded_ops.extend(self.grounded_ops().drain(0..).map(|(name, profile)| Atom::expr([
+            Atom::sym(name),
+            count("Calls", profile.calls),
+            time("ExecuteTimeUs", &profile.execute_time),
+        ])));
+        Atom::expr([PROFILE_SYMBOL, Atom::expr(functions), Atom::expr(grounded_ops)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiler_as_atom() {
+        let mut profiler = Profiler::new();
+        profiler.add_query(&expr!("foo" "a"), 2, Duration::from_micros(10));
+        profiler.add_query(&expr!("bar"), 1, Duration::from_micros(30));
+        profiler.add_query(&expr!("foo" "b"), 0, Duration::from_micros(5));
+        profiler.add_execute(&expr!("+" "a" "b"), Duration::from_micros(7));
+
+        assert_eq!(profiler.as_atom(), expr!("Profile"
+            ("Functions"
+                ("bar" ("Queries" "1") ("Reductions" "1") ("QueryTimeUs" "30"))
+                ("foo" ("Queries" "2") ("Reductions" "2") ("QueryTimeUs" "15")))
+            ("GroundedOps"
+                ("+" ("Calls" "1") ("ExecuteTimeUs" "7")))));
+    }
+
+    #[test]
+    fn profiler_reset() {
+        let mut profiler = Profiler::new();
+        profiler.add_query(&expr!("foo" "a"), 2, Duration::from_micros(10));
+        profiler.reset();
+
+        assert_eq!(profiler.as_atom(), expr!("Profile" ("Functions") ("GroundedOps")));
+    }
+}
@@ -0,0 +1,296 @@
+//! Operations which aggregate nondeterministic results of the atom
+//! interpretation into a deterministic result. Interpreter returns results
+//! only after the whole plan is finished, thus the atom should have a finite
+//! number of results. The exception is `limit` which sets
+//! [InterpreterOptions::max_results] to stop the interpretation after
+//! enough results are calculated. When one of the results is an `(Error ...)`
+//! expression then the first error is returned by any aggregation operation
+//! instead of the aggregated value, `sort-by` also returns the first error
+//! returned by the key function.
+
+use crate::*;
+use crate::matcher::MatchResultIter;
+use crate::metta::*;
+use crate::metta::interpreter::{interpret_with_options, InterpreterOptions};
+use super::RunnerOptions;
+use crate::metta::space::grounding::GroundingSpace;
+use crate::common::shared::Shared;
+use super::arithmetics::{Number, SumOp, ATOM_TYPE_NUMBER};
+use super::list::compare_atoms;
+use super::stdlib::interpret_nested;
+use super::typed::{arg, GroundedValue};
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+macro_rules! def_aggregate_op {
+    ($name:ident, $token:literal, $type:expr, $aggregate:ident) => {
+        #[derive(Clone, PartialEq, Debug)]
+        pub struct $name {
+            space: Shared<GroundingSpace>,
+            options: RunnerOptions,
+        }
+
+        impl $name {
+            pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+                Self{ space, options }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, $token)
+            }
+        }
+
+        impl Grounded for $name {
+            fn type_(&self) -> Atom {
+                $type
+            }
+
+            fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+                $aggregate(&self.space, &self.options, args)
+            }
+
+            fn match_(&self, other: &Atom) -> MatchResultIter {
+                match_by_equality(self, other)
+            }
+        }
+    }
+}
+
+/// Interprets the last argument which is expected at `index` and passes
+/// results to `aggregate`. When `max_results` is set then at most this
+/// number of results is calculated. The first `(Error ...)` result is
+/// returned instead of calling `aggregate`.
+fn aggregate<F>(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom],
+    index: usize, op: &str, max_results: Option<usize>, aggregate: F) -> Result<Vec<Atom>, ExecError>
+where
+    F: FnOnce(Vec<Atom>) -> Result<Vec<Atom>, ExecError>
+{
+    check_arity(args, index + 1, op)?;
+    let options = InterpreterOptions{ max_results, ..options.interpreter_options() };
+    let mut results = interpret_with_options(space.clone(), &args[index], options)?;
+    if let Some(max_results) = max_results {
+        results.truncate(max_results);
+    }
+    log::debug!("{}: atom: {}, results: {:?}", op, args[index], results);
+    match results.iter().find(|atom| atom_is_error(atom)) {
+        Some(error) => Ok(vec![error.clone()]),
+        None => aggregate(results),
+    }
+}
+
+fn check_arity(args: &[Atom], arity: usize, op: &str) -> Result<(), ExecError> {
+    match args.len() == arity {
+        true => Ok(()),
+        false => Err(format!("{} expects {} arguments, {} given", op, arity, args.len()).into()),
+    }
+}
+
+fn check_numbers(results: &[Atom], op: &str) -> Result<(), ExecError> {
+    match results.iter().find(|atom| Number::from_atom(atom).is_none()) {
+        Some(atom) => Err(format!("{} expects numbers as results, got {}", op, atom).into()),
+        None => Ok(()),
+    }
+}
+
+fn count(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    aggregate(space, options, args, 0, "count", None,
+        |results| Ok(vec![(results.len() as i64).into_atom()]))
+}
+
+fn sum(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    aggregate(space, options, args, 0, "sum", None, |numbers| {
+        check_numbers(&numbers, "sum")?;
+        numbers.into_iter()
+            .try_fold(0i64.into_atom(), |acc, n| SumOp{}.execute(&mut vec![acc, n]).map(|mut res| res.remove(0)))
+            .map(|sum| vec![sum])
+    })
+}
+
+fn extremum(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom], op: &str, ord: Ordering) -> Result<Vec<Atom>, ExecError> {
+    aggregate(space, options, args, 0, op, None, |numbers| {
+        check_numbers(&numbers, op)?;
+        Ok(numbers.into_iter().reduce(|a, b| {
+            let (na, nb) = (Number::from_atom(&a).unwrap(), Number::from_atom(&b).unwrap());
+            match nb.compare(&na) {
+                Some(o) if o == ord => b,
+                _ => a,
+            }
+        }).into_iter().collect())
+    })
+}
+
+fn min_of(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    extremum(space, options, args, "min-of", Ordering::Less)
+}
+
+fn max_of(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    extremum(space, options, args, "max-of", Ordering::Greater)
+}
+
+fn distinct(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    aggregate(space, options, args, 0, "distinct", None, |results| {
+        let mut distinct: Vec<Atom> = Vec::new();
+        for atom in results {
+            if !distinct.contains(&atom) {
+                distinct.push(atom);
+            }
+        }
+        Ok(distinct)
+    })
+}
+
+fn limit(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    let n = arg::<i64>(args, 0).map_err(|err| ExecError::from(format!("limit {}", err)))?;
+    let n = usize::try_from(n).map_err(|_| ExecError::from(format!("limit expects non-negative number, got {}", n)))?;
+    if n == 0 {
+        return check_arity(args, 2, "limit").map(|()| vec![]);
+    }
+    aggregate(space, options, args, 1, "limit", Some(n), Ok)
+}
+
+fn sort_by(space: &Shared<GroundingSpace>, options: &RunnerOptions, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    let key = args.first().ok_or("sort-by expects two arguments: key function and atom")?;
+    aggregate(space, options, args, 1, "sort-by", None, |results| {
+        let mut keyed = Vec::new();
+        for atom in results {
+            let mut keys = interpret_nested(space.clone(), &Atom::expr([key.clone(), atom.clone()]), options)?;
+            if keys.is_empty() {
+                return Err(format!("sort-by: key function returned no result for {}", atom).into());
+            }
+            if let Some(error) = keys.iter().find(|key| atom_is_error(key)) {
+                return Ok(vec![error.clone()]);
+            }
+            keyed.push((keys.swap_remove(0), atom));
+        }
+        keyed.sort_by(|(a, _), (b, _)| compare_atoms(a, b));
+        Ok(vec![Atom::expr(keyed.into_iter().map(|(_, atom)| atom).collect::<Vec<Atom>>())])
+    })
+}
+
+def_aggregate_op!(CountOp, "count", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_NUMBER]), count);
+def_aggregate_op!(SumAggregateOp, "sum", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_NUMBER]), sum);
+def_aggregate_op!(MinOfOp, "min-of", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_NUMBER]), min_of);
+def_aggregate_op!(MaxOfOp, "max-of", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_NUMBER]), max_of);
+def_aggregate_op!(DistinctOp, "distinct", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_UNDEFINED]), distinct);
+def_aggregate_op!(LimitOp, "limit", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_NUMBER, ATOM_TYPE_ATOM, ATOM_TYPE_UNDEFINED]), limit);
+def_aggregate_op!(SortByOp, "sort-by", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_ATOM, ATOM_TYPE_EXPRESSION]), sort_by);
+
+/// Returns aggregation operations which interpret atoms in the `space`, the
+/// name of each operation is its token.
+pub fn aggregate_ops(space: Shared<GroundingSpace>, options: RunnerOptions) -> Vec<Atom> {
+    vec![
+        Atom::gnd(CountOp::new(space.clone(), options.clone())),
+        Atom::gnd(SumAggregateOp::new(space.clone(), options.clone())),
+        Atom::gnd(MinOfOp::new(space.clone(), options.clone())),
+        Atom::gnd(MaxOfOp::new(space.clone(), options.clone())),
+        Atom::gnd(DistinctOp::new(space.clone(), options.clone())),
+        Atom::gnd(LimitOp::new(space.clone(), options.clone())),
+        Atom::gnd(SortByOp::new(space, options)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::stdlib::SuperposeOp;
+    use crate::common::Operation;
+
+    fn num(n: i64) -> Atom {
+        Atom::gnd(Number::Integer(n))
+    }
+
+    static ALTERNATIVES: &Operation = &Operation{
+        name: "alternatives",
+        execute: |_, args| Ok(args.clone()),
+        typ: "(-> Atom Atom Atom)",
+    };
+
+    fn space() -> Shared<GroundingSpace> {
+        let space = Shared::new(metta_space("
+            (= (color) red)
+            (= (color) green)
+            (= (color) red)
+            (= (size red) 3)
+            (= (size green) 1)
+            (= (no-size $x) (Error $x NoSize))
+            (= (loop) (loop))
+        "));
+        let empty = Atom::expr([Atom::gnd(SuperposeOp{}), expr!()]);
+        space.borrow_mut().add(Atom::expr([sym!("="), expr!(("empty")), empty]));
+        let endless = Atom::expr([Atom::gnd(ALTERNATIVES), num(1), expr!(("loop"))]);
+        space.borrow_mut().add(Atom::expr([sym!("="), expr!(("endless")), endless]));
+        for n in [5, -2, 7] {
+            space.borrow_mut().add(Atom::expr([sym!("="), expr!(("num")), num(n)]));
+        }
+        space
+    }
+
+    #[test]
+    fn count_and_distinct() {
+        let space = space();
+        assert_eq!(CountOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("color"))]), Ok(vec![num(3)]));
+        assert_eq!(CountOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("empty"))]), Ok(vec![num(0)]));
+        assert_eq_no_order!(DistinctOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("color"))]).unwrap(),
+            vec![sym!("red"), sym!("green")]);
+        assert_eq!(CountOp::new(space, RunnerOptions::default()).execute(&mut vec![]),
+            Err(ExecError::from("count expects 1 arguments, 0 given")));
+    }
+
+    #[test]
+    fn numeric_aggregates() {
+        let space = space();
+        assert_eq!(SumAggregateOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("num"))]), Ok(vec![num(10)]));
+        assert_eq!(SumAggregateOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("empty"))]), Ok(vec![num(0)]));
+        assert_eq!(MinOfOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("num"))]), Ok(vec![num(-2)]));
+        assert_eq!(MaxOfOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("num"))]), Ok(vec![num(7)]));
+        assert_eq!(MaxOfOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("empty"))]), Ok(vec![]));
+        assert_eq!(SumAggregateOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![expr!(("a"))]),
+            Err(ExecError::from("sum expects numbers as results, got (a)")));
+        assert_eq!(SumAggregateOp::new(space, RunnerOptions::default()).execute(&mut vec![expr!("Error" "a" "b")]),
+            Ok(vec![expr!("Error" "a" "b")]));
+    }
+
+    #[test]
+    fn limit_and_sort_by() {
+        let space = space();
+        assert_eq!(LimitOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![num(2), expr!(("num"))]).map(|res| res.len()), Ok(2));
+        assert_eq!(LimitOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![num(0), expr!(("num"))]), Ok(vec![]));
+        assert_eq!(LimitOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![num(-1), expr!(("num"))]),
+            Err(ExecError::from("limit expects non-negative number, got -1")));
+        assert_eq!(SortByOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![sym!("size"), expr!(("color"))]),
+            Ok(vec![expr!("green" "red" "red")]));
+        assert_eq!(SortByOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![sym!("size"), expr!("blue")]),
+            Ok(vec![expr!(("blue"))]));
+        let result = SortByOp::new(space, RunnerOptions::default()).execute(&mut vec![sym!("no-size"), expr!(("color"))]).unwrap();
+        assert!(result.len() == 1 && atom_is_error(&result[0]), "{:?}", result);
+    }
+
+    #[test]
+    fn limit_stops_interpretation() {
+        let space = space();
+        assert_eq!(LimitOp::new(space.clone(), RunnerOptions::default()).execute(&mut vec![num(1), expr!(("endless"))]), Ok(vec![num(1)]));
+        assert_eq!(LimitOp::new(space, RunnerOptions::default()).execute(&mut vec![num(0), expr!(("loop"))]), Ok(vec![]));
+    }
+
+    #[test]
+    fn aggregates_return_error() {
+        let space = space();
+        let error = expr!("Error" "a" "b");
+        for op in aggregate_ops(space, RunnerOptions::default()) {
+            let mut args = match op.to_string().as_str() {
+                "limit" => vec![num(2), error.clone()],
+                "sort-by" => vec![sym!("size"), error.clone()],
+                _ => vec![error.clone()],
+            };
+            let result = match &op {
+                Atom::Grounded(op) => op.execute(&mut args),
+                _ => unreachable!(),
+            };
+            assert_eq!(result, Ok(vec![error.clone()]), "{}", op);
+        }
+    }
+}
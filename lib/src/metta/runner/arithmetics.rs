@@ -1,54 +1,101 @@
 use crate::*;
 use crate::metta::*;
 use crate::matcher::MatchResultIter;
+use super::typed::{GroundedFn, GroundedValue};
 
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt::Display;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
 
 pub const ATOM_TYPE_NUMBER : Atom = sym!("Number");
 pub const ATOM_TYPE_BOOL : Atom = sym!("Bool");
 
-#[derive(Clone, PartialEq, Debug)]
+/// Integer which doesn't fit into `i64` is kept as [BigInt], integer
+/// operations are promoted to [BigInt] on overflow and result is converted
+/// back to `i64` when it fits, thus each integer has a single representation.
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(type = ATOM_TYPE_NUMBER)]
 pub enum Number {
     Integer(i64),
-    Float(f64), 
+    BigInt(BigInt),
+    Float(f64),
 }
 
 impl Number {
     pub fn from_int_str(num: &str) -> Self {
-        let n = num.parse::<i64>().expect("Could not parse integer");
-        Self::Integer(n)
+        Self::parse_int(num).expect("Could not parse integer")
     }
 
     pub fn from_float_str(num: &str) -> Self {
-        let n = num.parse::<f64>().expect("Could not parse float");
+        let n = num.replace('_', "").parse::<f64>().expect("Could not parse float");
         Self::Float(n)
     }
-}
 
-impl Display for Number {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Parses decimal or hexadecimal (`0x` prefix) integer with optional
+    /// minus sign, digits can be separated by underscores.
+    pub fn parse_int(num: &str) -> Option<Self> {
+        let (sign, digits) = match num.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", num),
+        };
+        let (radix, digits) = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(digits) => (16, digits),
+            None => (10, digits),
+        };
+        if digits.starts_with('_') || digits.starts_with('+') || digits.starts_with('-') {
+            return None;
+        }
+        let digits = format!("{}{}", sign, digits.replace('_', ""));
+        BigInt::parse_bytes(digits.as_bytes(), radix).map(Self::from_bigint)
+    }
+
+    pub fn from_bigint(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(n) => Self::Integer(n),
+            None => Self::BigInt(n),
+        }
+    }
+
+    pub fn to_bigint(&self) -> Option<BigInt> {
         match self {
-            Self::Integer(n) => write!(f, "{}", n),
-            Self::Float(n) => write!(f, "{}", n),
+            Self::Integer(n) => Some(BigInt::from(*n)),
+            Self::BigInt(n) => Some(n.clone()),
+            Self::Float(_) => None,
         }
     }
-}
 
-impl Grounded for Number {
-    fn type_(&self) -> Atom {
-        ATOM_TYPE_NUMBER
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Self::Integer(n) => *n as f64,
+            Self::BigInt(n) => n.to_f64().unwrap_or(f64::NAN),
+            Self::Float(n) => *n,
+        }
     }
 
-    fn execute(&self, _args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
-        execute_not_executable(self)
+    /// Compares numbers by value, integers are compared exactly.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(b)),
+            (Self::Float(_), _) | (_, Self::Float(_)) => self.to_f64().partial_cmp(&other.to_f64()),
+            _ => Some(self.to_bigint().cmp(&other.to_bigint())),
+        }
     }
+}
 
-    fn match_(&self, other: &Atom) -> MatchResultIter {
-        match_by_equality(self, other)
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::BigInt(n) => write!(f, "{}", n),
+            Self::Float(n) => write!(f, "{}", n),
+        }
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(type = ATOM_TYPE_BOOL)]
 pub struct Bool(bool);
 
 impl Bool {
@@ -70,22 +117,84 @@ impl Display for Bool {
     }
 }
 
-impl Grounded for Bool {
-    fn type_(&self) -> Atom {
+impl GroundedValue for Number {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_NUMBER
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        atom.as_gnd::<Number>().cloned()
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::gnd(self)
+    }
+}
+
+impl GroundedValue for i64 {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_NUMBER
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        match atom.as_gnd::<Number>() {
+            Some(Number::Integer(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::gnd(Number::Integer(self))
+    }
+}
+
+impl GroundedValue for f64 {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_NUMBER
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        atom.as_gnd::<Number>().map(Number::to_f64)
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::gnd(Number::Float(self))
+    }
+}
+
+impl GroundedValue for Bool {
+    fn type_atom() -> Atom {
         ATOM_TYPE_BOOL
     }
 
-    fn execute(&self, _args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
-        execute_not_executable(self)
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        atom.as_gnd::<Bool>().cloned()
     }
 
-    fn match_(&self, other: &Atom) -> MatchResultIter {
-        match_by_equality(self, other)
+    fn into_atom(self) -> Atom {
+        Atom::gnd(self)
     }
 }
 
+impl GroundedValue for bool {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_BOOL
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        atom.as_gnd::<Bool>().map(|b| b.0)
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::gnd(Bool(self))
+    }
+}
+
+/// Defines arithmetic operation. Integer operation is tried first using
+/// `$checked` method of `i64` and on overflow it is repeated using `$big`
+/// function on [BigInt] which returns `None` on division by zero.
 macro_rules! def_binary_number_op {
-    ($name:ident, $op:tt) => {
+    ($name:ident, $op:tt, $checked:ident, $big:expr) => {
         #[derive(Clone, PartialEq, Debug)]
         pub struct $name{}
 
@@ -105,11 +214,14 @@ macro_rules! def_binary_number_op {
                 let a = args.get(0).ok_or_else(arg_error)?.as_gnd::<Number>().ok_or_else(arg_error)?;
                 let b = args.get(1).ok_or_else(arg_error)?.as_gnd::<Number>().ok_or_else(arg_error)?;
 
+                let big: fn(&BigInt, &BigInt) -> Option<BigInt> = $big;
                 let res = match (a, b) {
-                    (Number::Integer(a), Number::Integer(b)) => Number::Integer(a $op b),
-                    (Number::Integer(a), Number::Float(b)) => Number::Float((*a as f64) $op b),
-                    (Number::Float(a), Number::Integer(b)) => Number::Float(a $op (*b as f64)),
-                    (Number::Float(a), Number::Float(b)) => Number::Float(a $op b),
+                    (Number::Float(_), _) | (_, Number::Float(_)) => Number::Float(a.to_f64() $op b.to_f64()),
+                    (Number::Integer(a), Number::Integer(b)) if a.$checked(*b).is_some() => Number::Integer(a $op b),
+                    _ => a.to_bigint().zip(b.to_bigint())
+                        .and_then(|(a, b)| big(&a, &b))
+                        .map(Number::from_bigint)
+                        .ok_or("Division by zero")?,
                 };
 
                 Ok(vec![Atom::gnd(res)])
@@ -122,11 +234,223 @@ macro_rules! def_binary_number_op {
     }
 }
 
-def_binary_number_op!(SumOp, +);
-def_binary_number_op!(SubOp, -);
-def_binary_number_op!(MulOp, *);
-def_binary_number_op!(DivOp, /);
-def_binary_number_op!(ModOp, %);
+def_binary_number_op!(SumOp, +, checked_add, |a, b| Some(a + b));
+def_binary_number_op!(SubOp, -, checked_sub, |a, b| Some(a - b));
+def_binary_number_op!(MulOp, *, checked_mul, |a, b| Some(a * b));
+def_binary_number_op!(DivOp, /, checked_div, |a, b| if b.is_zero() { None } else { Some(a / b) });
+def_binary_number_op!(ModOp, %, checked_rem, |a, b| if b.is_zero() { None } else { Some(a % b) });
+
+macro_rules! def_binary_number_cmp_op {
+    ($name:ident, $op:tt) => {
+        #[derive(Clone, PartialEq, Debug)]
+        pub struct $name{}
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, stringify!($op))
+            }
+        }
+
+        impl Grounded for $name {
+            fn type_(&self) -> Atom {
+                Atom::expr([ARROW_SYMBOL, ATOM_TYPE_NUMBER, ATOM_TYPE_NUMBER, ATOM_TYPE_BOOL])
+            }
+
+            fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+                let arg_error = || ExecError::from(concat!(stringify!($op), " expects two number arguments"));
+                let a = args.get(0).ok_or_else(arg_error)?.as_gnd::<Number>().ok_or_else(arg_error)?;
+                let b = args.get(1).ok_or_else(arg_error)?.as_gnd::<Number>().ok_or_else(arg_error)?;
+
+                let res = a.compare(b).map_or(false, |ord| ord $op Ordering::Equal);
+
+                Ok(vec![Atom::gnd(Bool(res))])
+            }
+
+            fn match_(&self, other: &Atom) -> MatchResultIter {
+                match_by_equality(self, other)
+            }
+        }
+    }
+}
+
+def_binary_number_cmp_op!(LessOp, <);
+def_binary_number_cmp_op!(GreaterOp, >);
+def_binary_number_cmp_op!(LessEqOp, <=);
+def_binary_number_cmp_op!(GreaterEqOp, >=);
+
+macro_rules! def_binary_bool_op {
+    ($name:ident, $disp:ident, $op:tt) => {
+        #[derive(Clone, PartialEq, Debug)]
+        pub struct $name{}
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, stringify!($disp))
+            }
+        }
+
+        impl Grounded for $name {
+            fn type_(&self) -> Atom {
+                Atom::expr([ARROW_SYMBOL, ATOM_TYPE_BOOL, ATOM_TYPE_BOOL, ATOM_TYPE_BOOL])
+            }
+
+            fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+                let arg_error = || ExecError::from(concat!(stringify!($disp), " expects two boolean arguments"));
+                let Bool(a) = args.get(0).ok_or_else(arg_error)?.as_gnd::<Bool>().ok_or_else(arg_error)?;
+                let Bool(b) = args.get(1).ok_or_else(arg_error)?.as_gnd::<Bool>().ok_or_else(arg_error)?;
+
+                Ok(vec![Atom::gnd(Bool(*a $op *b))])
+            }
+
+            fn match_(&self, other: &Atom) -> MatchResultIter {
+                match_by_equality(self, other)
+            }
+        }
+    }
+}
+
+def_binary_bool_op!(AndOp, and, &&);
+def_binary_bool_op!(OrOp, or, ||);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct NotOp{}
+
+impl Display for NotOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not")
+    }
+}
+
+impl Grounded for NotOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_BOOL, ATOM_TYPE_BOOL])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let arg_error = || ExecError::from("not expects one boolean argument");
+        let Bool(a) = args.first().ok_or_else(arg_error)?.as_gnd::<Bool>().ok_or_else(arg_error)?;
+
+        Ok(vec![Atom::gnd(Bool(!a))])
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+/// Polymorphic equality: numbers are compared by value, thus `(== 1 1.0)` is
+/// `True`, all other atoms are compared using [Atom] equality.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EqualOp{}
+
+impl Display for EqualOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "==")
+    }
+}
+
+impl Grounded for EqualOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, expr!(t), expr!(t), ATOM_TYPE_BOOL])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let arg_error = || ExecError::from("== expects two arguments");
+        let a = args.first().ok_or_else(arg_error)?;
+        let b = args.get(1).ok_or_else(arg_error)?;
+
+        let res = match (Number::from_atom(a), Number::from_atom(b)) {
+            (Some(a), Some(b)) => a.compare(&b) == Some(Ordering::Equal),
+            _ => a == b,
+        };
+        Ok(vec![Atom::gnd(Bool(res))])
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+fn abs(n: Number) -> Number {
+    match n {
+        Number::Integer(n) => n.checked_abs().map_or_else(|| Number::from_bigint(BigInt::from(n).abs()), Number::Integer),
+        Number::BigInt(n) => Number::BigInt(n.abs()),
+        Number::Float(n) => Number::Float(n.abs()),
+    }
+}
+
+fn min(a: Number, b: Number) -> Number {
+    match a.compare(&b) {
+        Some(Ordering::Greater) => b,
+        _ => a,
+    }
+}
+
+fn max(a: Number, b: Number) -> Number {
+    match a.compare(&b) {
+        Some(Ordering::Less) => b,
+        _ => a,
+    }
+}
+
+/// Maximum number of bits in the integer result of `pow`.
+pub const MAX_POW_RESULT_BITS: u64 = 1 << 20;
+
+/// Integer power is calculated exactly when exponent is non-negative,
+/// otherwise result is float. Integer power returns error when its result is
+/// estimated to have more than [MAX_POW_RESULT_BITS] bits.
+fn pow(base: Number, exp: Number) -> Result<Number, ExecError> {
+    match (base.to_bigint(), &exp) {
+        (Some(base), Number::Integer(exp)) if *exp >= 0 => {
+            let exp = u32::try_from(*exp)
+                .map_err(|_| ExecError::from(format!("pow: exponent {} is too large", exp)))?;
+            if base.bits() > 1 && base.bits().saturating_mul(exp as u64) > MAX_POW_RESULT_BITS {
+                return Err(format!("pow: result of {} to the power of {} is too large", base, exp).into());
+            }
+            Ok(Number::from_bigint(base.pow(exp)))
+        },
+        _ => Ok(Number::Float(base.to_f64().powf(exp.to_f64()))),
+    }
+}
+
+/// Converts float into integer using `round` function, integer is returned
+/// as is.
+fn to_int(name: &str, n: Number, round: fn(f64) -> f64) -> Result<Number, ExecError> {
+    match n {
+        Number::Float(f) => BigInt::from_f64(round(f)).map(Number::from_bigint)
+            .ok_or_else(|| ExecError::from(format!("{}: cannot convert {} to integer", name, f))),
+        n => Ok(n),
+    }
+}
+
+/// Returns mathematical functions on numbers, the name of each function is
+/// its token.
+pub fn math_ops() -> Vec<Atom> {
+    vec![
+        Atom::gnd(GroundedFn::new("abs", abs)),
+        Atom::gnd(GroundedFn::new("min", min)),
+        Atom::gnd(GroundedFn::new("max", max)),
+        Atom::gnd(GroundedFn::new("pow", pow)),
+        Atom::gnd(GroundedFn::new("sqrt", f64::sqrt)),
+        Atom::gnd(GroundedFn::new("exp", f64::exp)),
+        Atom::gnd(GroundedFn::new("log", f64::ln)),
+        Atom::gnd(GroundedFn::new("sin", f64::sin)),
+        Atom::gnd(GroundedFn::new("cos", f64::cos)),
+        Atom::gnd(GroundedFn::new("tan", f64::tan)),
+        Atom::gnd(GroundedFn::new("asin", f64::asin)),
+        Atom::gnd(GroundedFn::new("acos", f64::acos)),
+        Atom::gnd(GroundedFn::new("atan", f64::atan)),
+        Atom::gnd(GroundedFn::new("atan2", f64::atan2)),
+        Atom::gnd(GroundedFn::new("floor", |n: Number| to_int("floor", n, f64::floor))),
+        Atom::gnd(GroundedFn::new("ceil", |n: Number| to_int("ceil", n, f64::ceil))),
+        Atom::gnd(GroundedFn::new("round", |n: Number| to_int("round", n, f64::round))),
+        Atom::gnd(GroundedFn::new("truncate", |n: Number| to_int("truncate", n, f64::trunc))),
+        Atom::gnd(GroundedFn::new("to-int", |n: Number| to_int("to-int", n, f64::trunc))),
+        Atom::gnd(GroundedFn::new("to-float", |n: f64| n)),
+        Atom::gnd(GroundedFn::new("isnan", f64::is_nan)),
+        Atom::gnd(GroundedFn::new("isinf", f64::is_infinite)),
+    ]
+}
 
 #[cfg(test)]
 mod tests {
@@ -142,6 +466,21 @@ mod tests {
         assert_eq!(format!("{}", Number::Float(123.45f64)), "123.45");
     }
 
+    #[test]
+    fn number_parse_int() {
+        assert_eq!(Number::parse_int("-42"), Some(Number::Integer(-42)));
+        assert_eq!(Number::parse_int("1_000_000"), Some(Number::Integer(1000000)));
+        assert_eq!(Number::parse_int("0xFF"), Some(Number::Integer(255)));
+        assert_eq!(Number::parse_int("-0x1_0"), Some(Number::Integer(-16)));
+        assert_eq!(Number::parse_int("9223372036854775808"),
+            Some(Number::BigInt("9223372036854775808".parse().unwrap())));
+        assert_eq!(Number::parse_int("-9223372036854775808"), Some(Number::Integer(i64::MIN)));
+        assert_eq!(Number::parse_int("--1"), None);
+        assert_eq!(Number::parse_int("+1"), None);
+        assert_eq!(Number::parse_int("0x"), None);
+        assert_eq!(Number::from_float_str("-1_000.5"), Number::Float(-1000.5));
+    }
+
     #[test]
     fn bool() {
         assert_eq!(Bool::from_str("True"), Bool(true));
@@ -188,6 +527,44 @@ mod tests {
         assert_number_binary_op!(DivOp, Number::Float(430.5), Number::Float(10.25), Number::Float(42.0));
     }
 
+    macro_rules! assert_binary_op {
+        ($name:ident, $a: expr, $b: expr, $r: expr) => {
+            assert_eq!($name{}.execute(&mut vec![Atom::gnd($a), Atom::gnd($b)]), Ok(vec![Atom::gnd($r)]));
+        }
+    }
+
+    #[test]
+    fn number_cmp_ops() {
+        assert_binary_op!(LessOp, Number::Integer(1), Number::Integer(2), Bool(true));
+        assert_binary_op!(LessOp, Number::Float(2.5), Number::Integer(2), Bool(false));
+        assert_binary_op!(GreaterOp, Number::Integer(3), Number::Float(2.5), Bool(true));
+        assert_binary_op!(GreaterOp, Number::Integer(2), Number::Integer(2), Bool(false));
+        assert_binary_op!(LessEqOp, Number::Integer(2), Number::Float(2.0), Bool(true));
+        assert_binary_op!(GreaterEqOp, Number::Float(1.5), Number::Float(2.0), Bool(false));
+        assert_eq!(LessOp{}.execute(&mut vec![Atom::gnd(Bool(true)), Atom::gnd(Number::Integer(2))]),
+            Err(ExecError::from("< expects two number arguments")));
+    }
+
+    #[test]
+    fn bool_ops() {
+        assert_binary_op!(AndOp, Bool(true), Bool(false), Bool(false));
+        assert_binary_op!(AndOp, Bool(true), Bool(true), Bool(true));
+        assert_binary_op!(OrOp, Bool(true), Bool(false), Bool(true));
+        assert_binary_op!(OrOp, Bool(false), Bool(false), Bool(false));
+        assert_eq!(NotOp{}.execute(&mut vec![Atom::gnd(Bool(false))]), Ok(vec![Atom::gnd(Bool(true))]));
+        assert_eq!(OrOp{}.execute(&mut vec![Atom::gnd(Bool(true))]),
+            Err(ExecError::from("or expects two boolean arguments")));
+    }
+
+    #[test]
+    fn equal_op() {
+        assert_binary_op!(EqualOp, Number::Integer(2), Number::Float(2.0), Bool(true));
+        assert_binary_op!(EqualOp, Number::Integer(2), Number::Integer(3), Bool(false));
+        assert_binary_op!(EqualOp, Bool(true), Bool(true), Bool(true));
+        assert_eq!(EqualOp{}.execute(&mut vec![expr!("a" "b"), expr!("a" "b")]), Ok(vec![Atom::gnd(Bool(true))]));
+        assert_eq!(EqualOp{}.execute(&mut vec![expr!("a"), Atom::gnd(Number::Integer(2))]), Ok(vec![Atom::gnd(Bool(false))]));
+    }
+
     #[test]
     fn mod_op() {
         assert_number_binary_op!(ModOp, Number::Integer(85), Number::Integer(43), Number::Integer(42));
@@ -195,4 +572,80 @@ mod tests {
         assert_number_binary_op!(ModOp, Number::Float(85.5), Number::Integer(43), Number::Float(42.5));
         assert_number_binary_op!(ModOp, Number::Float(85.5), Number::Float(43.5), Number::Float(42.0));
     }
+
+    #[test]
+    fn number_overflow_promotion() {
+        let big = |s: &str| Number::BigInt(s.parse().unwrap());
+        assert_number_binary_op!(SumOp, Number::Integer(i64::MAX), Number::Integer(1), big("9223372036854775808"));
+        assert_number_binary_op!(SubOp, big("9223372036854775808"), Number::Integer(1), Number::Integer(i64::MAX));
+        assert_number_binary_op!(MulOp, Number::Integer(1 << 62), Number::Integer(-4), big("-18446744073709551616"));
+        assert_number_binary_op!(DivOp, Number::Integer(i64::MIN), Number::Integer(-1), big("9223372036854775808"));
+        assert_number_binary_op!(ModOp, Number::Integer(i64::MIN), Number::Integer(-1), Number::Integer(0));
+        assert_number_binary_op!(SumOp, big("9223372036854775808"), Number::Float(0.5), Number::Float(9223372036854775808.5));
+        assert_binary_op!(LessOp, Number::Integer(i64::MAX), big("9223372036854775808"), Bool(true));
+        assert_binary_op!(EqualOp, big("9223372036854775808"), Number::Float(9223372036854775808.0), Bool(true));
+    }
+
+    #[test]
+    fn number_division_by_zero() {
+        assert_eq!(DivOp{}.execute(&mut vec![Atom::gnd(Number::Integer(1)), Atom::gnd(Number::Integer(0))]),
+            Err(ExecError::from("Division by zero")));
+        assert_eq!(ModOp{}.execute(&mut vec![Atom::gnd(Number::BigInt(BigInt::from(u64::MAX))), Atom::gnd(Number::Integer(0))]),
+            Err(ExecError::from("Division by zero")));
+        assert_number_binary_op!(DivOp, Number::Integer(1), Number::Float(0.0), Number::Float(f64::INFINITY));
+    }
+
+    fn math_op(name: &str, args: &[Number]) -> Result<Vec<Atom>, ExecError> {
+        let mut args = args.iter().cloned().map(Atom::gnd).collect();
+        match math_ops().into_iter().find(|op| op.to_string() == name) {
+            Some(Atom::Grounded(op)) => op.execute(&mut args),
+            _ => unreachable!(),
+        }
+    }
+
+    fn assert_math_op(name: &str, args: &[Number], expected: Number) {
+        assert_eq!(math_op(name, args), Ok(vec![Atom::gnd(expected)]), "{}", name);
+    }
+
+    #[test]
+    fn math_ops_integer() {
+        assert_math_op("abs", &[Number::Integer(-3)], Number::Integer(3));
+        assert_math_op("abs", &[Number::Integer(i64::MIN)], Number::BigInt(BigInt::from(i64::MIN).abs()));
+        assert_math_op("abs", &[Number::Float(-2.5)], Number::Float(2.5));
+        assert_math_op("min", &[Number::Integer(3), Number::Float(2.5)], Number::Float(2.5));
+        assert_math_op("max", &[Number::Integer(3), Number::Float(2.5)], Number::Integer(3));
+        assert_math_op("pow", &[Number::Integer(2), Number::Integer(10)], Number::Integer(1024));
+        assert_math_op("pow", &[Number::Integer(2), Number::Integer(64)], Number::BigInt(BigInt::from(u64::MAX) + 1));
+        assert_math_op("pow", &[Number::Integer(2), Number::Integer(-1)], Number::Float(0.5));
+        assert_math_op("pow", &[Number::Float(4.0), Number::Float(0.5)], Number::Float(2.0));
+        assert_math_op("pow", &[Number::Integer(-1), Number::Integer(4000000000)], Number::Integer(1));
+        assert_eq!(math_op("pow", &[Number::Integer(10), Number::Integer(4000000000)]),
+            Err(ExecError::from("pow: result of 10 to the power of 4000000000 is too large")));
+    }
+
+    #[test]
+    fn math_ops_float() {
+        assert_math_op("sqrt", &[Number::Integer(16)], Number::Float(4.0));
+        assert_math_op("exp", &[Number::Integer(0)], Number::Float(1.0));
+        assert_math_op("log", &[Number::Integer(1)], Number::Float(0.0));
+        assert_math_op("sin", &[Number::Integer(0)], Number::Float(0.0));
+        assert_math_op("cos", &[Number::Integer(0)], Number::Float(1.0));
+        assert_math_op("atan2", &[Number::Integer(0), Number::Integer(1)], Number::Float(0.0));
+        assert_math_op("to-float", &[Number::Integer(3)], Number::Float(3.0));
+        assert_eq!(math_op("isnan", &[Number::Float(f64::NAN)]), Ok(vec![Atom::gnd(Bool(true))]));
+        assert_eq!(math_op("isinf", &[Number::Float(1.0)]), Ok(vec![Atom::gnd(Bool(false))]));
+        assert_eq!(math_op("sqrt", &[]), Err(ExecError::from("sqrt expects 1 arguments, 0 given")));
+    }
+
+    #[test]
+    fn math_ops_rounding() {
+        assert_math_op("floor", &[Number::Float(-2.5)], Number::Integer(-3));
+        assert_math_op("ceil", &[Number::Float(-2.5)], Number::Integer(-2));
+        assert_math_op("round", &[Number::Float(2.5)], Number::Integer(3));
+        assert_math_op("truncate", &[Number::Float(-2.7)], Number::Integer(-2));
+        assert_math_op("to-int", &[Number::Float(1e20)], Number::BigInt(BigInt::from(10u64).pow(20)));
+        assert_math_op("round", &[Number::Integer(7)], Number::Integer(7));
+        assert_eq!(math_op("to-int", &[Number::Float(f64::INFINITY)]),
+            Err(ExecError::from("to-int: cannot convert inf to integer")));
+    }
 }
@@ -0,0 +1,189 @@
+//! Capabilities control which groups of the stdlib operations are allowed to
+//! be executed. They are used to run untrusted MeTTa code: operation of the
+//! disabled group returns `(Error <call> (PermissionDenied <group>))` atom
+//! instead of being executed. Sandbox root limits the paths which are
+//! accessible by the file system operations.
+
+use crate::*;
+use crate::matcher::MatchResultIter;
+use crate::metta::*;
+use crate::common::shared::Shared;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::fmt::Display;
+
+pub const PERMISSION_DENIED_SYMBOL : Atom = sym!("PermissionDenied");
+
+/// Group of the operations which have side effects outside of the
+/// interpreted expression.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Capability {
+    /// Reading and writing files, `file-read`, `file-write` and other file
+    /// system operations.
+    FileSystem,
+    /// Writing into the standard output, `println!`.
+    Console,
+    /// Loading modules, `import!`.
+    ModuleLoading,
+    /// Changing value of the state atom, `change-state!`.
+    StateMutation,
+    /// Adding and removing atoms of the space, `add-atom` and `remove-atom`.
+    SpaceMutation,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 5] = [Capability::FileSystem, Capability::Console,
+        Capability::ModuleLoading, Capability::StateMutation, Capability::SpaceMutation];
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Capability::FileSystem => write!(f, "file-system"),
+            Capability::Console => write!(f, "console"),
+            Capability::ModuleLoading => write!(f, "module-loading"),
+            Capability::StateMutation => write!(f, "state-mutation"),
+            Capability::SpaceMutation => write!(f, "space-mutation"),
+        }
+    }
+}
+
+/// Set of the enabled capabilities and the sandbox root. [Default] value
+/// has all capabilities enabled and no sandbox root.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Capabilities {
+    enabled: HashSet<Capability>,
+    sandbox_root: Option<PathBuf>,
+}
+
+impl Capabilities {
+    /// Returns capabilities with all groups of operations enabled.
+    pub fn all() -> Self {
+        Self{ enabled: Capability::ALL.iter().cloned().collect(), sandbox_root: None }
+    }
+
+    /// Returns capabilities with all groups of operations disabled.
+    pub fn none() -> Self {
+        Self{ enabled: HashSet::new(), sandbox_root: None }
+    }
+
+    pub fn enable(&mut self, capability: Capability) {
+        self.enabled.insert(capability);
+    }
+
+    pub fn disable(&mut self, capability: Capability) {
+        self.enabled.remove(&capability);
+    }
+
+    pub fn set(&mut self, capability: Capability, enabled: bool) {
+        match enabled {
+            true => self.enable(capability),
+            false => self.disable(capability),
+        }
+    }
+
+    pub fn is_enabled(&self, capability: Capability) -> bool {
+        self.enabled.contains(&capability)
+    }
+
+    /// Sets directory outside of which file system operations are not
+    /// permitted, `None` means any path is permitted.
+    pub fn set_sandbox_root(&mut self, root: Option<PathBuf>) {
+        self.sandbox_root = root;
+    }
+
+    pub fn sandbox_root(&self) -> Option<&Path> {
+        self.sandbox_root.as_deref()
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Wraps grounded operation and executes it only when `capability` is
+/// enabled. Capabilities are checked on each call thus they can be changed
+/// after the tokens are registered.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RestrictedOp {
+    op: Atom,
+    capability: Capability,
+    capabilities: Shared<Capabilities>,
+}
+
+impl RestrictedOp {
+    pub fn new(op: Atom, capability: Capability, capabilities: Shared<Capabilities>) -> Self {
+        assert!(matches!(op, Atom::Grounded(_)), "Grounded operation is expected: {}", op);
+        Self{ op, capability, capabilities }
+    }
+
+    fn op(&self) -> &dyn GroundedAtom {
+        match &self.op {
+            Atom::Grounded(op) => op.as_ref(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Display for RestrictedOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.op)
+    }
+}
+
+impl Grounded for RestrictedOp {
+    fn type_(&self) -> Atom {
+        self.op().type_()
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        if self.capabilities.borrow().is_enabled(self.capability) {
+            self.op().execute(args)
+        } else {
+            log::debug!("RestrictedOp::execute: {} is not permitted, {} capability is disabled", self.op, self.capability);
+            let mut call = vec![Atom::gnd(self.clone())];
+            call.append(args);
+            let reason = Atom::expr([PERMISSION_DENIED_SYMBOL, Atom::sym(self.capability.to_string())]);
+            Ok(vec![Atom::expr([ERROR_SYMBOL, Atom::expr(call), reason])])
+        }
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::stdlib::PrintlnOp;
+
+    #[test]
+    fn capabilities_enable_disable() {
+        let mut capabilities = Capabilities::default();
+        assert!(Capability::ALL.iter().all(|c| capabilities.is_enabled(*c)));
+        capabilities.disable(Capability::Console);
+        assert!(!capabilities.is_enabled(Capability::Console));
+        assert!(capabilities.is_enabled(Capability::FileSystem));
+        capabilities.set(Capability::Console, true);
+        assert_eq!(capabilities, Capabilities::all());
+        assert!(Capability::ALL.iter().all(|c| !Capabilities::none().is_enabled(*c)));
+    }
+
+    #[test]
+    fn restricted_op_checks_capability_on_each_call() {
+        let capabilities = Shared::new(Capabilities::none());
+        let op = RestrictedOp::new(Atom::gnd(PrintlnOp{}), Capability::Console, capabilities.clone());
+
+        assert_eq!(op.to_string(), "println!");
+        assert_eq!(op.type_(), PrintlnOp{}.type_());
+        assert_eq!(op.execute(&mut vec![sym!("a")]), Ok(vec![Atom::expr([ERROR_SYMBOL,
+            Atom::expr([Atom::gnd(op.clone()), sym!("a")]), expr!("PermissionDenied" "console")])]));
+
+        capabilities.borrow_mut().enable(Capability::Console);
+        assert_eq!(op.execute(&mut vec![sym!("a")]), PrintlnOp{}.execute(&mut vec![sym!("a")]));
+    }
+}
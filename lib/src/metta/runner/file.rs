@@ -0,0 +1,256 @@
+//! File system operations. Relative paths are resolved against the directory
+//! of the module which is interpreted. When sandbox root is set in
+//! [Capabilities] then paths outside of the root are not permitted. Symbolic
+//! links are resolved before the check thus they cannot be used to leave the
+//! sandbox, paths containing dangling symbolic links are not permitted.
+
+use crate::*;
+use crate::matcher::MatchResultIter;
+use crate::metta::*;
+use crate::common::shared::Shared;
+use super::arithmetics::ATOM_TYPE_BOOL;
+use super::string::ATOM_TYPE_STRING;
+use super::capabilities::Capabilities;
+use super::typed::{arg, GroundedValue};
+
+use std::fmt::Display;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves paths passed to the file system operations.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Files {
+    cwd: PathBuf,
+    capabilities: Shared<Capabilities>,
+}
+
+impl Files {
+    pub fn new(cwd: PathBuf, capabilities: Shared<Capabilities>) -> Self {
+        Self{ cwd, capabilities }
+    }
+
+    /// Returns path relative to the `cwd` with symbolic links resolved,
+    /// returns error when path is outside of the sandbox root.
+    fn resolve(&self, path: &str, op: &str) -> Result<PathBuf, ExecError> {
+        let resolved = real_path(&self.cwd.join(path))
+            .ok_or_else(|| ExecError::from(format!("{}: cannot resolve path {:?}", op, path)))?;
+        if let Some(root) = self.capabilities.borrow().sandbox_root() {
+            let root = real_path(root)
+                .ok_or_else(|| ExecError::from(format!("{}: cannot resolve sandbox root {}", op, root.display())))?;
+            if !resolved.starts_with(root) {
+                return Err(format!("{}: path {:?} is outside of the sandbox root", op, path).into());
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+/// Canonicalizes the longest existing prefix of the path and appends the
+/// rest of the path to it. Returns `None` when the rest contains `..` or
+/// when a missing part of the path is a dangling symbolic link, otherwise
+/// writing to the link would create the file it points to.
+fn real_path(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        let dir = if existing.as_os_str().is_empty() { Path::new(".") } else { existing };
+        if let Ok(real) = dir.canonicalize() {
+            return Some(missing.iter().rev().fold(real, |path, name| path.join(name)));
+        }
+        if fs::symlink_metadata(dir).is_ok() {
+            return None;
+        }
+        match existing.components().next_back() {
+            Some(Component::Normal(name)) => {
+                missing.push(name);
+                existing = existing.parent()?;
+            },
+            Some(Component::CurDir) => existing = existing.parent()?,
+            _ => return None,
+        }
+    }
+}
+
+fn io_error(op: &str, path: &str, err: std::io::Error) -> ExecError {
+    format!("{}: {:?}: {}", op, path, err).into()
+}
+
+fn string_arg(args: &[Atom], index: usize, op: &str) -> Result<String, ExecError> {
+    arg::<String>(args, index).map_err(|err| format!("{} {}", op, err).into())
+}
+
+macro_rules! def_file_op {
+    ($name:ident, $token:literal, $type:expr, $execute:ident) => {
+        #[derive(Clone, PartialEq, Debug)]
+        pub struct $name {
+            files: Files,
+        }
+
+        impl $name {
+            pub fn new(files: Files) -> Self {
+                Self{ files }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, $token)
+            }
+        }
+
+        impl Grounded for $name {
+            fn type_(&self) -> Atom {
+                $type
+            }
+
+            fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+                $execute(&self.files, args)
+            }
+
+            fn match_(&self, other: &Atom) -> MatchResultIter {
+                match_by_equality(self, other)
+            }
+        }
+    }
+}
+
+fn read(files: &Files, args: &[Atom]) -> Result<String, ExecError> {
+    let path = string_arg(args, 0, "file-read")?;
+    fs::read_to_string(files.resolve(&path, "file-read")?)
+        .map_err(|err| io_error("file-read", &path, err))
+}
+
+fn file_read(files: &Files, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    Ok(vec![read(files, args)?.into_atom()])
+}
+
+fn file_read_lines(files: &Files, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    let lines: Vec<Atom> = read(files, args)?.lines().map(|line| line.to_string().into_atom()).collect();
+    Ok(vec![Atom::expr(lines)])
+}
+
+fn write(files: &Files, args: &[Atom], op: &str, append: bool) -> Result<Vec<Atom>, ExecError> {
+    let path = string_arg(args, 0, op)?;
+    let content = string_arg(args, 1, op)?;
+    let mut file = fs::OpenOptions::new().create(true).write(true).append(append).truncate(!append)
+        .open(files.resolve(&path, op)?)
+        .map_err(|err| io_error(op, &path, err))?;
+    file.write_all(content.as_bytes()).map_err(|err| io_error(op, &path, err))?;
+    Ok(vec![])
+}
+
+fn file_write(files: &Files, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    write(files, args, "file-write", false)
+}
+
+fn file_append(files: &Files, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    write(files, args, "file-append", true)
+}
+
+fn dir_list(files: &Files, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    let path = string_arg(args, 0, "dir-list")?;
+    let mut names = fs::read_dir(files.resolve(&path, "dir-list")?)
+        .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<String>, std::io::Error>>())
+        .map_err(|err| io_error("dir-list", &path, err))?;
+    names.sort();
+    Ok(vec![Atom::expr(names.into_iter().map(String::into_atom).collect::<Vec<Atom>>())])
+}
+
+fn file_exists(files: &Files, args: &[Atom]) -> Result<Vec<Atom>, ExecError> {
+    let path = string_arg(args, 0, "file-exists")?;
+    Ok(vec![files.resolve(&path, "file-exists")?.exists().into_atom()])
+}
+
+def_file_op!(FileReadOp, "file-read", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_STRING, ATOM_TYPE_STRING]), file_read);
+def_file_op!(FileReadLinesOp, "file-read-lines", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_STRING, ATOM_TYPE_EXPRESSION]), file_read_lines);
+def_file_op!(FileWriteOp, "file-write", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_STRING, ATOM_TYPE_STRING, sym!("IO")]), file_write);
+def_file_op!(FileAppendOp, "file-append", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_STRING, ATOM_TYPE_STRING, sym!("IO")]), file_append);
+def_file_op!(DirListOp, "dir-list", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_STRING, ATOM_TYPE_EXPRESSION]), dir_list);
+def_file_op!(FileExistsOp, "file-exists", Atom::expr([ARROW_SYMBOL, ATOM_TYPE_STRING, ATOM_TYPE_BOOL]), file_exists);
+
+/// Returns file system operations which resolve paths using `files`, the
+/// name of each operation is its token.
+pub fn file_ops(files: Files) -> Vec<Atom> {
+    vec![
+        Atom::gnd(FileReadOp::new(files.clone())),
+        Atom::gnd(FileReadLinesOp::new(files.clone())),
+        Atom::gnd(FileWriteOp::new(files.clone())),
+        Atom::gnd(FileAppendOp::new(files.clone())),
+        Atom::gnd(DirListOp::new(files.clone())),
+        Atom::gnd(FileExistsOp::new(files)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str_atom(s: &str) -> Atom {
+        s.to_string().into_atom()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hyperon-file-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn file_ops_read_write() {
+        let dir = temp_dir("read-write");
+        let files = Files::new(dir.clone(), Shared::new(Capabilities::default()));
+
+        assert_eq!(FileWriteOp::new(files.clone()).execute(&mut vec![str_atom("a.txt"), str_atom("x\n")]), Ok(vec![]));
+        assert_eq!(FileAppendOp::new(files.clone()).execute(&mut vec![str_atom("a.txt"), str_atom("y\n")]), Ok(vec![]));
+        assert_eq!(FileReadOp::new(files.clone()).execute(&mut vec![str_atom("a.txt")]), Ok(vec![str_atom("x\ny\n")]));
+        assert_eq!(FileReadLinesOp::new(files.clone()).execute(&mut vec![str_atom("./sub/../a.txt")]),
+            Ok(vec![Atom::expr([str_atom("x"), str_atom("y")])]));
+        assert_eq!(FileExistsOp::new(files.clone()).execute(&mut vec![str_atom("a.txt")]), Ok(vec![true.into_atom()]));
+        assert_eq!(FileExistsOp::new(files.clone()).execute(&mut vec![str_atom("b.txt")]), Ok(vec![false.into_atom()]));
+        assert_eq!(DirListOp::new(files.clone()).execute(&mut vec![str_atom(".")]),
+            Ok(vec![Atom::expr([str_atom("a.txt"), str_atom("sub")])]));
+        assert!(FileReadOp::new(files).execute(&mut vec![str_atom("b.txt")]).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn file_ops_sandbox_root() {
+        let dir = temp_dir("sandbox");
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        let capabilities = Shared::new(Capabilities::default());
+        capabilities.borrow_mut().set_sandbox_root(Some(dir.join("sub")));
+        let files = Files::new(dir.join("sub"), capabilities);
+
+        assert_eq!(FileWriteOp::new(files.clone()).execute(&mut vec![str_atom("b.txt"), str_atom("b")]), Ok(vec![]));
+        assert_eq!(FileReadOp::new(files.clone()).execute(&mut vec![str_atom("b.txt")]), Ok(vec![str_atom("b")]));
+        assert_eq!(FileReadOp::new(files.clone()).execute(&mut vec![str_atom("../a.txt")]),
+            Err(ExecError::from("file-read: path \"../a.txt\" is outside of the sandbox root")));
+        assert!(FileExistsOp::new(files.clone()).execute(&mut vec![str_atom(&dir.join("a.txt").to_string_lossy())]).is_err());
+        assert!(FileWriteOp::new(files).execute(&mut vec![str_atom("c/../../c.txt"), str_atom("c")]).is_err());
+        assert!(!dir.join("c.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_ops_sandbox_dangling_symlink() {
+        let dir = temp_dir("dangling-symlink");
+        std::os::unix::fs::symlink(dir.join("d.txt"), dir.join("sub").join("d.txt")).unwrap();
+        let capabilities = Shared::new(Capabilities::default());
+        capabilities.borrow_mut().set_sandbox_root(Some(dir.join("sub")));
+        let files = Files::new(dir.join("sub"), capabilities);
+
+        assert_eq!(FileWriteOp::new(files.clone()).execute(&mut vec![str_atom("d.txt"), str_atom("d")]),
+            Err(ExecError::from("file-write: cannot resolve path \"d.txt\"")));
+        assert!(FileAppendOp::new(files.clone()).execute(&mut vec![str_atom("./d.txt"), str_atom("d")]).is_err());
+        assert!(FileWriteOp::new(files).execute(&mut vec![str_atom("d.txt/e.txt"), str_atom("e")]).is_err());
+        assert!(!dir.join("d.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
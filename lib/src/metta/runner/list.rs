@@ -0,0 +1,308 @@
+use crate::*;
+use crate::matcher::MatchResultIter;
+use crate::metta::*;
+use super::RunnerOptions;
+use crate::metta::space::grounding::GroundingSpace;
+use crate::common::shared::Shared;
+use super::arithmetics::Number;
+use super::stdlib::interpret_nested;
+use super::typed::{GroundedFn, GroundedValue};
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+fn nth(list: Vec<Atom>, index: i64) -> Result<Atom, ExecError> {
+    usize::try_from(index).ok().and_then(|index| list.get(index)).cloned()
+        .ok_or_else(|| format!("list-nth: index {} is out of range for list of length {}", index, list.len()).into())
+}
+
+/// Order of atoms used by `list-sort`: numbers are compared by value, other
+/// atoms are compared by text representation, strings are compared without
+/// quotes.
+pub fn compare_atoms(a: &Atom, b: &Atom) -> Ordering {
+    match (Number::from_atom(a), Number::from_atom(b)) {
+        (Some(a), Some(b)) => a.compare(&b)
+            .unwrap_or_else(|| a.to_f64().total_cmp(&b.to_f64())),
+        _ => sort_key(a).cmp(&sort_key(b)),
+    }
+}
+
+fn sort_key(atom: &Atom) -> String {
+    String::from_atom(atom).unwrap_or_else(|| atom.to_string())
+}
+
+fn sort(mut list: Vec<Atom>) -> Vec<Atom> {
+    list.sort_by(compare_atoms);
+    list
+}
+
+fn unique(list: Vec<Atom>) -> Vec<Atom> {
+    let mut result: Vec<Atom> = Vec::new();
+    for atom in list {
+        if !result.contains(&atom) {
+            result.push(atom);
+        }
+    }
+    result
+}
+
+/// Maximum length of the list returned by `list-range`.
+pub const MAX_RANGE_LENGTH: i128 = 1_000_000;
+
+fn range(start: i64, end: i64) -> Result<Vec<Atom>, ExecError> {
+    if end as i128 - start as i128 > MAX_RANGE_LENGTH {
+        return Err(format!("list-range: length of range {}..{} exceeds {}", start, end, MAX_RANGE_LENGTH).into());
+    }
+    Ok((start..end).map(|n| Atom::gnd(Number::Integer(n))).collect())
+}
+
+/// Returns list operations which don't need interpreter, the name of each
+/// operation is its token.
+pub fn list_ops() -> Vec<Atom> {
+    vec![
+        Atom::gnd(GroundedFn::new("list-length", |list: Vec<Atom>| list.len() as i64)),
+        Atom::gnd(GroundedFn::new("list-nth", nth)),
+        Atom::gnd(GroundedFn::new("list-append", |mut a: Vec<Atom>, b: Vec<Atom>| { a.extend(b); a })),
+        Atom::gnd(GroundedFn::new("list-reverse", |mut list: Vec<Atom>| { list.reverse(); list })),
+        Atom::gnd(GroundedFn::new("list-sort", sort)),
+        Atom::gnd(GroundedFn::new("list-unique", unique)),
+        Atom::gnd(GroundedFn::new("list-range", range)),
+    ]
+}
+
+fn list_arg<'a>(args: &'a [Atom], index: usize, op: &str) -> Result<&'a [Atom], ExecError> {
+    match args.get(index) {
+        Some(Atom::Expression(expr)) => Ok(expr.children().as_slice()),
+        _ => Err(format!("{} expects expression as argument {}", op, index + 1).into()),
+    }
+}
+
+fn apply(space: &Shared<GroundingSpace>, options: &RunnerOptions, func: &Atom, args: &[&Atom]) -> Result<Vec<Atom>, ExecError> {
+    let mut call = vec![func.clone()];
+    call.extend(args.iter().map(|arg| (*arg).clone()));
+    Ok(interpret_nested(space.clone(), &Atom::expr(call), options)?)
+}
+
+/// Implements `(list-map <func> <list>)` operation. `(<func> <item>)` is
+/// interpreted for each item of the list. When function returns few
+/// results for an item then a list is returned for each combination of
+/// results.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ListMapOp {
+    space: Shared<GroundingSpace>,
+    options: RunnerOptions,
+}
+
+impl ListMapOp {
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
+    }
+}
+
+impl Display for ListMapOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "list-map")
+    }
+}
+
+impl Grounded for ListMapOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_EXPRESSION, ATOM_TYPE_EXPRESSION])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let func = args.first().ok_or("list-map expects two arguments: function and expression")?;
+        let mut lists = vec![vec![]];
+        for item in list_arg(args, 1, "list-map")? {
+            let results = apply(&self.space, &self.options, func, &[item])?;
+            lists = lists.into_iter()
+                .flat_map(|list| results.iter().map(move |res| {
+                    let mut list = list.clone();
+                    list.push(res.clone());
+                    list
+                }))
+                .collect();
+        }
+        Ok(lists.into_iter().map(Atom::expr).collect())
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+/// Implements `(list-filter <predicate> <list>)` operation. Item is kept when
+/// one of the results of `(<predicate> <item>)` is `True`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ListFilterOp {
+    space: Shared<GroundingSpace>,
+    options: RunnerOptions,
+}
+
+impl ListFilterOp {
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
+    }
+}
+
+impl Display for ListFilterOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "list-filter")
+    }
+}
+
+impl Grounded for ListFilterOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_EXPRESSION, ATOM_TYPE_EXPRESSION])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let predicate = args.first().ok_or("list-filter expects two arguments: predicate and expression")?;
+        let mut result = Vec::new();
+        for item in list_arg(args, 1, "list-filter")? {
+            let passed = apply(&self.space, &self.options, predicate, &[item])?.iter()
+                .any(|res| bool::from_atom(res) == Some(true) || *res == sym!("True"));
+            if passed {
+                result.push(item.clone());
+            }
+        }
+        Ok(vec![Atom::expr(result)])
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+/// Implements `(list-foldl <func> <init> <list>)` operation. Accumulator is
+/// initialized by `<init>` and replaced by the result of `(<func>
+/// <accumulator> <item>)` for each item of the list. Each result of the
+/// function is folded separately.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ListFoldlOp {
+    space: Shared<GroundingSpace>,
+    options: RunnerOptions,
+}
+
+impl ListFoldlOp {
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
+    }
+}
+
+impl Display for ListFoldlOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "list-foldl")
+    }
+}
+
+impl Grounded for ListFoldlOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_UNDEFINED, ATOM_TYPE_EXPRESSION, ATOM_TYPE_UNDEFINED])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let arg_error = || ExecError::from("list-foldl expects three arguments: function, initial value and expression");
+        let func = args.first().ok_or_else(arg_error)?;
+        let init = args.get(1).ok_or_else(arg_error)?;
+        let mut accs = vec![init.clone()];
+        for item in list_arg(args, 2, "list-foldl")? {
+            let mut next = Vec::new();
+            for acc in &accs {
+                next.extend(apply(&self.space, &self.options, func, &[acc, item])?);
+            }
+            accs = next;
+        }
+        Ok(accs)
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::string::Str;
+
+    fn num(n: i64) -> Atom {
+        Atom::gnd(Number::Integer(n))
+    }
+
+    fn execute(name: &str, mut args: Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        match list_ops().into_iter().find(|op| op.to_string() == name) {
+            Some(Atom::Grounded(gnd)) => gnd.execute(&mut args),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn list_ops_structure() {
+        assert_eq!(execute("list-length", vec![expr!("a" "b" "c")]), Ok(vec![num(3)]));
+        assert_eq!(execute("list-length", vec![expr!()]), Ok(vec![num(0)]));
+        assert_eq!(execute("list-nth", vec![expr!("a" "b"), num(1)]), Ok(vec![sym!("b")]));
+        assert_eq!(execute("list-nth", vec![expr!("a" "b"), num(-1)]),
+            Err(ExecError::from("list-nth: index -1 is out of range for list of length 2")));
+        assert_eq!(execute("list-append", vec![expr!(("a")), expr!("b" "c")]), Ok(vec![expr!("a" "b" "c")]));
+        assert_eq!(execute("list-reverse", vec![expr!("a" ("b") "c")]), Ok(vec![expr!("c" ("b") "a")]));
+        assert_eq!(execute("list-range", vec![num(1), num(4)]), Ok(vec![Atom::expr([num(1), num(2), num(3)])]));
+        assert_eq!(execute("list-range", vec![num(4), num(1)]), Ok(vec![expr!()]));
+        assert_eq!(execute("list-range", vec![num(0), num(10000000000)]),
+            Err(ExecError::from("list-range: length of range 0..10000000000 exceeds 1000000")));
+        assert!(execute("list-range", vec![num(i64::MIN), num(i64::MAX)]).is_err());
+    }
+
+    #[test]
+    fn list_ops_sort_unique() {
+        assert_eq!(execute("list-sort", vec![Atom::expr([num(10), Atom::gnd(Number::Float(2.5)), num(3)])]),
+            Ok(vec![Atom::expr([Atom::gnd(Number::Float(2.5)), num(3), num(10)])]));
+        assert_eq!(execute("list-sort", vec![Atom::expr([num((1 << 53) + 1), num(1 << 53)])]),
+            Ok(vec![Atom::expr([num(1 << 53), num((1 << 53) + 1)])]));
+        let str_atom = |s: &str| Atom::gnd(Str::from_string(s.into()));
+        assert_eq!(execute("list-sort", vec![Atom::expr([str_atom("b"), sym!("c"), str_atom("a")])]),
+            Ok(vec![Atom::expr([str_atom("a"), str_atom("b"), sym!("c")])]));
+        assert_eq!(execute("list-unique", vec![expr!("a" "b" "a" ("c") ("c"))]), Ok(vec![expr!("a" "b" ("c"))]));
+    }
+
+    fn space(text: &str) -> Shared<GroundingSpace> {
+        Shared::new(metta_space(text))
+    }
+
+    #[test]
+    fn list_map_op() {
+        let space = space("
+            (= (twice $x) ($x $x))
+            (= (either $x) $x)
+            (= (either $x) (not $x))
+        ");
+        let map = ListMapOp::new(space.clone(), RunnerOptions::default());
+
+        assert_eq!(map.execute(&mut vec![sym!("twice"), expr!("a" "b")]), Ok(vec![expr!(("a" "a") ("b" "b"))]));
+        assert_eq!(map.execute(&mut vec![sym!("twice"), expr!()]), Ok(vec![expr!()]));
+        assert_eq!(map.execute(&mut vec![sym!("either"), expr!("a" "b")]).map(|res| res.len()), Ok(4));
+    }
+
+    #[test]
+    fn list_filter_op() {
+        let space = space("
+            (= (is-a a) True)
+            (= (is-a b) False)
+        ");
+        let filter = ListFilterOp::new(space, RunnerOptions::default());
+
+        assert_eq!(filter.execute(&mut vec![sym!("is-a"), expr!("a" "b" "a" "c")]), Ok(vec![expr!("a" "a")]));
+    }
+
+    #[test]
+    fn list_foldl_op() {
+        let space = space("(= (push $list $x) ($x $list))");
+        let foldl = ListFoldlOp::new(space, RunnerOptions::default());
+
+        assert_eq!(foldl.execute(&mut vec![sym!("push"), expr!(), expr!("a" "b")]), Ok(vec![expr!("b" ("a" ()))]));
+        assert_eq!(foldl.execute(&mut vec![sym!("push"), sym!("z"), expr!()]), Ok(vec![sym!("z")]));
+        assert_eq!(foldl.execute(&mut vec![sym!("push"), sym!("z")]),
+            Err(ExecError::from("list-foldl expects expression as argument 3")));
+    }
+}
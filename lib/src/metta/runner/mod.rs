@@ -4,13 +4,23 @@ use crate::common::shared::Shared;
 use super::*;
 use super::space::grounding::GroundingSpace;
 use super::text::{Tokenizer, SExprParser};
-use super::types::validate_atom;
-use super::interpreter::interpret;
+use super::types::{validate_atom_detailed, validate_atom_strict, check_rule, check_rule_strict};
+use super::interpreter::{interpret_with_options, InterpreterOptions, CancellationToken};
+use super::profiler::Profiler;
+use capabilities::Capabilities;
+use super::inference::infer_types;
+use super::type_index::TypeIndex;
 
 use std::path::PathBuf;
 use std::collections::HashMap;
 
 pub mod stdlib;
+pub mod typed;
+pub mod string;
+pub mod list;
+pub mod aggregate;
+pub mod capabilities;
+pub mod file;
 
 mod arithmetics;
 
@@ -22,6 +32,42 @@ pub struct Metta {
     tokenizer: Shared<Tokenizer>,
     settings: Shared<HashMap<String, String>>,
     modules: Shared<HashMap<PathBuf, Shared<GroundingSpace>>>,
+    profiler: Shared<Profiler>,
+    capabilities: Shared<Capabilities>,
+    cancellation: CancellationToken,
+    type_index: TypeIndex,
+}
+
+/// Interpreter options which follow the settings of the runner. Grounded
+/// operations which interpret atoms by themselves use them to profile, cancel
+/// and collect call stack of the nested interpretation the same way as the
+/// runner does. Settings are read on each call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunnerOptions {
+    settings: Shared<HashMap<String, String>>,
+    profiler: Shared<Profiler>,
+    cancellation: CancellationToken,
+}
+
+impl RunnerOptions {
+    /// Returns interpreter options according to the current settings.
+    pub fn interpreter_options(&self) -> InterpreterOptions {
+        let enabled = |key: &str| self.settings.borrow().get(key).map(String::as_str) == Some("true");
+        let call_stack = enabled("call-stack");
+        let profiler = if enabled("profile") { Some(self.profiler.clone()) } else { None };
+        let cancellation = Some(self.cancellation.clone());
+        InterpreterOptions{ call_stack, profiler, cancellation, ..Default::default() }
+    }
+}
+
+impl Default for RunnerOptions {
+    fn default() -> Self {
+        Self{
+            settings: Shared::new(HashMap::new()),
+            profiler: Shared::new(Profiler::new()),
+            cancellation: CancellationToken::new(),
+        }
+    }
 }
 
 enum Mode {
@@ -37,7 +83,11 @@ impl Metta {
     pub fn from_space_cwd(space: Shared<GroundingSpace>, tokenizer: Shared<Tokenizer>, cwd: PathBuf) -> Self {
         let settings = Shared::new(HashMap::new());
         let modules = Shared::new(HashMap::new());
-        let metta = Self{ space, tokenizer, settings, modules };
+        let profiler = Shared::new(Profiler::new());
+        let capabilities = Shared::new(Capabilities::default());
+        let cancellation = CancellationToken::new();
+        let type_index = TypeIndex::new(space.clone());
+        let metta = Self{ space, tokenizer, settings, modules, profiler, capabilities, cancellation, type_index };
         stdlib::register_runner_tokens(&metta, cwd);
         stdlib::register_common_tokens(&metta);
         metta
@@ -50,7 +100,11 @@ impl Metta {
         next_cwd.pop();
         let settings = metta.settings.clone();
         let modules = metta.modules.clone();
-        let metta = Metta{ space, tokenizer, settings, modules };
+        let profiler = metta.profiler.clone();
+        let capabilities = metta.capabilities.clone();
+        let cancellation = metta.cancellation.clone();
+        let type_index = TypeIndex::new(space.clone());
+        let metta = Metta{ space, tokenizer, settings, modules, profiler, capabilities, cancellation, type_index };
         stdlib::register_runner_tokens(&metta, next_cwd);
         metta
     }
@@ -103,6 +157,39 @@ impl Metta {
         self.tokenizer.clone()
     }
 
+    /// Returns profiler which collects interpretation statistics when
+    /// profiling is enabled by `!(pragma! profile true)`.
+    pub fn profiler(&self) -> Shared<Profiler> {
+        self.profiler.clone()
+    }
+
+    /// Returns capabilities which enable groups of the stdlib operations.
+    /// All capabilities are enabled by default, they are shared with the
+    /// modules loaded by this instance and checked on each call of the
+    /// operation.
+    pub fn capabilities(&self) -> Shared<Capabilities> {
+        self.capabilities.clone()
+    }
+
+    /// Returns token which cancels interpretation of the expressions run by
+    /// this instance. Cancelled interpretation returns `(Error <atom>
+    /// Cancelled)` result and [Metta::run] stops after it. Token is not
+    /// reset automatically, call [CancellationToken::reset] before the next
+    /// run.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Returns options of the nested interpretation started by the grounded
+    /// operations.
+    pub fn runner_options(&self) -> RunnerOptions {
+        RunnerOptions{
+            settings: self.settings.clone(),
+            profiler: self.profiler.clone(),
+            cancellation: self.cancellation.clone(),
+        }
+    }
+
     #[cfg(test)]
     fn set_setting(&self, key: String, value: String) {
         self.settings.borrow_mut().insert(key, value);
@@ -135,15 +222,7 @@ impl Metta {
                         Mode::INTERPRET => match self.evaluate_atom(atom) {
                             Err(msg) => return Err(msg),
                             Ok(result) => {
-                                fn is_error(atom: &Atom) -> bool {
-                                    match atom {
-                                        Atom::Expression(expr) => expr.children()[0] == ERROR_SYMBOL,
-                                        _ => false,
-                                    }
-                                }
-                                let error = result.iter()
-                                    .map(|atom| is_error(atom))
-                                    .fold(false, |a, b| a | b);
+                                let error = result.iter().any(atom_is_error);
                                 results.push(result);
                                 if error {
                                     break
@@ -159,30 +238,117 @@ impl Metta {
         Ok(results)
     }
 
+    /// Reads the module from `path` and checks types of its `=` rules, see
+    /// [Metta::check].
+    pub fn check_module(&self, path: PathBuf) -> Result<Vec<Atom>, String> {
+        let program = std::fs::read_to_string(&path).map_err(
+            |err| format!("Could not read file, path: {}, error: {}", path.display(), err))?;
+        Ok(self.check(&mut SExprParser::new(program.as_str())))
+    }
+
+    /// Checks types of all `=` rules of the program without evaluating
+    /// anything. Atoms of the program are added into a separate space which
+    /// also includes the space of this instance, thus the order of type
+    /// declarations doesn't matter. Expressions to be executed are skipped,
+    /// including `!(import! ...)`, thus declarations of the imported modules
+    /// are not visible and rules which use them can be reported as typed
+    /// incorrectly; import such modules into this instance before the check.
+    /// Rules are checked strictly when `!(pragma! type-check strict)` is set.
+    /// Returns `(Error <rule> BadType <details>)` atom for each rule which is
+    /// typed incorrectly.
+    pub fn check(&self, parser: &mut SExprParser) -> Vec<Atom> {
+        let mut space = GroundingSpace::new();
+        space.add(Atom::gnd(self.space.clone()));
+        let mut rules = Vec::new();
+        let mut mode = Mode::ADD;
+        while let Some(atom) = parser.parse(&self.tokenizer.borrow()) {
+            if atom == EXEC_SYMBOL {
+                mode = Mode::INTERPRET;
+                continue;
+            }
+            if let Mode::ADD = mode {
+                if is_rule(&atom) {
+                    rules.push(atom.clone());
+                }
+                space.add(atom);
+            }
+            mode = Mode::ADD;
+        }
+        let check_rule = if self.is_strict_type_check() { check_rule_strict } else { check_rule };
+        rules.drain(0..)
+            .filter_map(|rule| match check_rule(&space, &rule) {
+                Ok(()) => None,
+                Err(err) => Some(Atom::expr([ERROR_SYMBOL, rule, BAD_TYPE_SYMBOL, err.as_atom()])),
+            }).collect()
+    }
+
+    /// Infers types of the functions which are defined in the space of this
+    /// instance by `=` rules only, see
+    /// [super::inference::infer_types]. Returns `(: <function>
+    /// <type>)` atom for each type inferred and `(Error <function> <reason>)`
+    /// atom for each function which cannot be typed. When `add` is true the
+    /// type declarations inferred are added into the space.
+    pub fn infer_types(&self, add: bool) -> Vec<Atom> {
+        let mut results = infer_types(&self.type_index);
+        let mut report = Vec::new();
+        for (function, result) in results.drain(0..) {
+            match result {
+                Ok(mut types) => types.drain(0..).for_each(|typ| {
+                    let decl = Atom::expr([HAS_TYPE_SYMBOL, function.clone(), typ]);
+                    if add {
+                        self.space.borrow_mut().add(decl.clone());
+                    }
+                    report.push(decl);
+                }),
+                Err(err) => report.push(Atom::expr([ERROR_SYMBOL, function, err.as_atom()])),
+            }
+        }
+        report
+    }
+
     pub fn evaluate_atom(&self, atom: Atom) -> Result<Vec<Atom>, String> {
         match self.type_check(atom) {
             Err(atom) => Ok(vec![atom]),
-            Ok(atom) => interpret(self.space.clone(), &atom),
+            Ok(atom) => interpret_with_options(self.type_index.clone(), &atom, self.interpreter_options()),
         }
     }
 
+    fn interpreter_options(&self) -> InterpreterOptions {
+        self.runner_options().interpreter_options()
+    }
+
     fn add_atom(&self, atom: Atom) -> Result<(), Atom>{
         let atom = self.type_check(atom)?;
         self.space.borrow_mut().add(atom);
         Ok(())
     }
 
+    fn is_strict_type_check(&self) -> bool {
+        self.get_setting("type-check").map_or(false, |val| val == "strict")
+    }
+
     fn type_check(&self, atom: Atom) -> Result<Atom, Atom> {
-        let is_type_check_enabled = self.get_setting("type-check").map_or(false, |val| val == "auto");
-        if  is_type_check_enabled && !validate_atom(self.space.borrow().deref(), &atom) {
-            Err(Atom::expr([ERROR_SYMBOL, atom, BAD_TYPE_SYMBOL]))
-        } else {
-            Ok(atom)
+        let type_check = self.get_setting("type-check");
+        let validate_atom = match type_check.as_deref() {
+            Some("auto") => validate_atom_detailed,
+            Some("strict") => validate_atom_strict,
+            _ => return Ok(atom),
+        };
+        match validate_atom(&self.type_index, &atom) {
+            Err(err) => Err(Atom::expr([ERROR_SYMBOL, atom, BAD_TYPE_SYMBOL, err.as_atom()])),
+            Ok(()) => Ok(atom),
         }
     }
 
 }
 
+fn is_rule(atom: &Atom) -> bool {
+    match atom {
+        Atom::Expression(expr) => expr.children().len() == 3 && expr.children()[0] == EQUAL_SYMBOL,
+        _ => false,
+    }
+}
+
 pub fn new_metta_rust() -> Metta {
     let metta = Metta::new(Shared::new(GroundingSpace::new()),
         Shared::new(Tokenizer::new()));
@@ -224,7 +390,27 @@ mod tests {
         let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
         metta.set_setting("type-check".into(), "auto".into());
         let result = metta.run(&mut SExprParser::new(program));
-        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "b") "BadType")]]));
+        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "b") "BadType"
+            ("BadFunctionCall" ("foo" "b") ("Candidate" ("->" "A" "B")
+                ("BadArgType" "1" ("Expected" "A") ("Actual" "B")))))]]));
+    }
+
+    #[test]
+    fn metta_strict_type_check() {
+        let program = "
+            (: foo (-> A B))
+            (: a A)
+            (foo a)
+            (foo typo)
+        ";
+
+        let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
+        metta.set_setting("type-check".into(), "strict".into());
+        let result = metta.run(&mut SExprParser::new(program));
+        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "typo") "BadType"
+            ("BadFunctionCall" ("foo" "typo") ("Candidate" ("->" "A" "B")
+                ("BadArgType" "1" ("Expected" "A") ("Actual" "%Undefined%")))))]]));
+        assert_eq!(metta.space().borrow().query(&expr!("foo" "a")), vec![crate::matcher::Bindings::new()]);
     }
 
     #[test]
@@ -238,7 +424,9 @@ mod tests {
         let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
         metta.set_setting("type-check".into(), "auto".into());
         let result = metta.run(&mut SExprParser::new(program));
-        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "b") "BadType")]]));
+        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "b") "BadType"
+            ("BadFunctionCall" ("foo" "b") ("Candidate" ("->" "A" "B")
+                ("BadArgType" "1" ("Expected" "A") ("Actual" "B")))))]]));
     }
 
     #[derive(Clone, PartialEq, Debug)]
@@ -280,6 +468,180 @@ mod tests {
         assert_eq!(result, Ok(vec![vec![expr!("Error" ("error") "TestError")]]));
     }
 
+    #[derive(Clone, PartialEq, Debug)]
+    struct RuntimeErrorOp{}
+
+    impl std::fmt::Display for RuntimeErrorOp {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "runtime-error")
+        }
+    }
+
+    impl Grounded for RuntimeErrorOp {
+        fn type_(&self) -> Atom {
+            Atom::expr([ARROW_SYMBOL, ATOM_TYPE_UNDEFINED])
+        }
+        fn execute(&self, _args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+            Err("TestError".into())
+        }
+        fn match_(&self, other: &Atom) -> crate::matcher::MatchResultIter {
+            match_by_equality(self, other)
+        }
+    }
+
+    #[test]
+    fn metta_return_error_with_call_stack() {
+        let program = "
+            (= (foo) (bar))
+            (= (bar) (runtime-error))
+            !(pragma! call-stack true)
+            !(foo)
+        ";
+
+        let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
+        metta.tokenizer().borrow_mut().register_token(Regex::new("runtime-error").unwrap(),
+            |_| Atom::gnd(RuntimeErrorOp{}));
+        let result = metta.run(&mut SExprParser::new(program));
+
+        assert_eq!(result, Ok(vec![vec![], vec![expr!("Error" ({RuntimeErrorOp{}}) "TestError"
+            ("CallStack" ("foo") ("bar")))]]));
+    }
+
+    #[test]
+    fn metta_profile_functions() {
+        let program = "
+            (= (foo) (bar))
+            (= (bar) (runtime-error))
+            !(pragma! profile true)
+            !(foo)
+        ";
+
+        let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
+        metta.tokenizer().borrow_mut().register_token(Regex::new("runtime-error").unwrap(),
+            |_| Atom::gnd(RuntimeErrorOp{}));
+        metta.run(&mut SExprParser::new(program)).unwrap();
+        let profiler = metta.profiler();
+        let profiler = profiler.borrow();
+
+        let functions: Vec<(&str, usize, usize)> = profiler.functions().iter()
+            .map(|(name, profile)| (name.as_str(), profile.queries, profile.reductions)).collect();
+        assert_eq_no_order!(functions, vec![("foo", 1, 1), ("bar", 1, 1)]);
+        let grounded_ops: Vec<(&str, usize)> = profiler.grounded_ops().iter()
+            .map(|(name, profile)| (name.as_str(), profile.calls)).collect();
+        assert_eq!(grounded_ops, vec![("runtime-error", 1)]);
+    }
+
+    #[test]
+    fn metta_profile_nested_interpretation() {
+        let program = "
+            (= (foo) (bar))
+            (= (bar) ok)
+            !(pragma! profile true)
+            !(collapse (foo))
+        ";
+
+        let metta = new_metta_rust();
+        metta.run(&mut SExprParser::new(program)).unwrap();
+        let profiler = metta.profiler();
+        let profiler = profiler.borrow();
+
+        let functions: Vec<(&str, usize, usize)> = profiler.functions().iter()
+            .map(|(name, profile)| (name.as_str(), profile.queries, profile.reductions)).collect();
+        assert_eq_no_order!(functions, vec![("foo", 1, 1), ("bar", 1, 1), ("ok", 1, 0)]);
+        let grounded_ops: Vec<(&str, usize)> = profiler.grounded_ops().iter()
+            .map(|(name, profile)| (name.as_str(), profile.calls)).collect();
+        assert_eq!(grounded_ops, vec![("collapse", 1)]);
+    }
+
+    #[test]
+    fn metta_run_cancelled() {
+        let program = "
+            (= (foo) (bar))
+            (= (bar) ok)
+            !(foo)
+            !(bar)
+        ";
+
+        let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
+        metta.cancellation_token().cancel();
+        let result = metta.run(&mut SExprParser::new(program));
+        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo") "Cancelled")]]));
+
+        metta.cancellation_token().reset();
+        let result = metta.run(&mut SExprParser::new("!(foo)"));
+        assert_eq!(result, Ok(vec![vec![sym!("ok")]]));
+    }
+
+    #[test]
+    fn metta_run_cancels_nested_interpretation() {
+        let metta = new_metta_rust();
+        metta.run(&mut SExprParser::new("(= (loop) (loop))")).unwrap();
+
+        for program in ["!(case (loop) ((else done)))", "!(collapse (loop))", "!(count (loop))"] {
+            let token = metta.cancellation_token();
+            token.reset();
+            let canceller = std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                token.cancel();
+            });
+            let result = metta.run(&mut SExprParser::new(program));
+            canceller.join().unwrap();
+
+            let result = result.unwrap();
+            assert_eq!(result.len(), 1, "{}", program);
+            assert!(result[0].iter().all(|atom| atom_is_error(atom) && atom.to_string().contains("Cancelled")),
+                "{}: {:?}", program, result);
+        }
+    }
+
+    #[test]
+    fn metta_check_reports_all_rules() {
+        let program = "
+            (= (foo) (bar b))
+            (= (foo) b)
+            (= (bar $x) (foo))
+            !(foo)
+            (: foo (-> A))
+            (: bar (-> B A))
+            (= (baz) (bar a))
+            (: a A)
+            (: b B)
+        ";
+
+        let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
+        let errors = metta.check(&mut SExprParser::new(program));
+
+        assert_eq!(errors, vec![expr!("Error" ("=" ("foo") "b") "BadType"
+            ("BadType" "b" ("Expected" "A") ("Actual" "B"))),
+            expr!("Error" ("=" ("baz") ("bar" "a")) "BadType"
+            ("BadFunctionCall" ("bar" "a") ("Candidate" ("->" "B" "A")
+                ("BadArgType" "1" ("Expected" "B") ("Actual" "A")))))]);
+        assert!(metta.space().borrow().query(&expr!("=" ("foo") x)).is_empty());
+    }
+
+    #[test]
+    fn metta_infer_types_adds_declarations() {
+        let program = "
+            (: foo (-> A B))
+            (: a A)
+            (= (bar $x) (foo $x))
+            (: b B)
+            (= (f) a)
+            (= (f) b)
+        ";
+
+        let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
+        metta.run(&mut SExprParser::new(program)).unwrap();
+        let report = metta.infer_types(true);
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0], expr!(":" "bar" ("->" "A" "B")));
+        assert!(matches!(&report[1], Atom::Expression(expr)
+            if expr.children()[0] == ERROR_SYMBOL && expr.children()[1] == expr!("f")));
+        assert_eq!(metta.run(&mut SExprParser::new("!(get-type bar)")),
+            Ok(vec![vec![expr!("->" "A" "B")]]));
+    }
+
     #[test]
     fn metta_stop_after_type_check_fails_on_add() {
         let program = "
@@ -293,6 +655,8 @@ mod tests {
         let metta = Metta::new(Shared::new(GroundingSpace::new()), Shared::new(Tokenizer::new()));
         metta.set_setting("type-check".into(), "auto".into());
         let result = metta.run(&mut SExprParser::new(program));
-        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "b") "BadType")]]));
+        assert_eq!(result, Ok(vec![vec![expr!("Error" ("foo" "b") "BadType"
+            ("BadFunctionCall" ("foo" "b") ("Candidate" ("->" "A" "B")
+                ("BadArgType" "1" ("Expected" "A") ("Actual" "B")))))]]));
     }
 }
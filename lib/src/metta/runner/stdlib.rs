@@ -1,11 +1,12 @@
 use crate::*;
-use crate::matcher::MatchResultIter;
+use crate::matcher::{MatchResultIter, match_atoms, apply_bindings_to_atom};
 use crate::metta::*;
 use crate::metta::space::grounding::GroundingSpace;
 use crate::metta::text::Tokenizer;
-use crate::metta::interpreter::interpret;
-use crate::metta::runner::Metta;
+use crate::metta::interpreter::interpret_with_options;
+use crate::metta::runner::{Metta, RunnerOptions};
 use crate::metta::types::get_atom_types;
+use crate::metta::profiler::Profiler;
 use crate::common::shared::Shared;
 use crate::common::assert::vec_eq_no_order;
 
@@ -18,13 +19,25 @@ use std::iter::FromIterator;
 use regex::Regex;
 
 use super::arithmetics::*;
+use super::string::{Str, string_ops};
+use super::list::{list_ops, ListMapOp, ListFilterOp, ListFoldlOp};
+use super::aggregate::aggregate_ops;
+use super::capabilities::{Capability, RestrictedOp};
+use super::file::{file_ops, Files};
+use super::typed::GroundedValue;
 
 pub const VOID_SYMBOL : Atom = sym!("%void%");
 
+/// Interprets `expr` in the nested interpreter of the grounded operation
+/// using current `options` of the runner.
+pub fn interpret_nested(space: Shared<GroundingSpace>, expr: &Atom, options: &RunnerOptions) -> Result<Vec<Atom>, String> {
+    interpret_with_options(space, expr, options.interpreter_options())
+}
+
 // TODO: remove hiding errors completely after making it possible passing
 // them to the user
-fn interpret_no_error(space: Shared<GroundingSpace>, expr: &Atom) -> Result<Vec<Atom>, String> {
-    let result = interpret(space, expr);
+fn interpret_no_error(space: Shared<GroundingSpace>, expr: &Atom, options: &RunnerOptions) -> Result<Vec<Atom>, String> {
+    let result = interpret_nested(space, expr, options);
     log::debug!("interpret_no_error: interpretation expr: {}, result {:?}", expr, result);
     match result {
         Ok(result) => Ok(result),
@@ -379,28 +392,89 @@ impl Grounded for ConsAtomOp {
     }
 }
 
+/// Pattern of the `case` branch which is used when result is empty or none
+/// of other patterns is matched.
+pub const CASE_ELSE_SYMBOL : Atom = sym!("else");
+
+/// Implements `(case <atom> (<case>...))` operation. `<atom>` is
+/// interpreted and each result is matched against patterns of the cases
+/// in order. Case is either `(<pattern> <template>)` or `(<pattern> <guard>
+/// <template>)`. When result is matched and guard is evaluated to `True`
+/// then template is returned with the bindings of the match applied.
+/// `(else <template>)` case is used when result is empty or it is not
+/// matched by other cases. `(%void% <template>)` case is used only when
+/// result is empty. `(Error ...)` results of the `<atom>` and of the guards
+/// are returned as is, they are not matched against cases.
 #[derive(Clone, PartialEq, Debug)]
 pub struct CaseOp {
     space: Shared<GroundingSpace>,
+    options: RunnerOptions,
+}
+
+struct Case {
+    pattern: Atom,
+    guard: Option<Atom>,
+    template: Atom,
+}
+
+impl Case {
+    fn parse(atom: &Atom) -> Result<Self, ExecError> {
+        let arg_error = || ExecError::from("case expects (pattern template) or (pattern guard template) as a case");
+        // variables of the case should not clash with the variables of the result
+        match atom_as_expr(&make_variables_unique(atom)).ok_or_else(arg_error)?.children().as_slice() {
+            [pattern, template] => Ok(Self{ pattern: pattern.clone(), guard: None, template: template.clone() }),
+            [pattern, guard, template] => Ok(Self{ pattern: pattern.clone(), guard: Some(guard.clone()), template: template.clone() }),
+            _ => Err(arg_error()),
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        self.pattern == CASE_ELSE_SYMBOL || self.pattern == VOID_SYMBOL
+    }
 }
 
 impl CaseOp {
-    pub fn new(space: Shared<GroundingSpace>) -> Self {
-        Self{ space }
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
     }
 
-    fn first_case_matched(atom: &Atom, cases: &ExpressionAtom) -> Result<Vec<Atom>, ExecError> {
-        let mut space = GroundingSpace::new();
-        space.add(atom.clone());
-        for c in cases.children() {
-            log::debug!("CaseOp::first_case_matched: next case: {}", c);
-            let next_case = atom_as_expr(c).ok_or("case expects expression of pairs as a second argument")?.children();
-            let result = space.subst(&next_case[0], &next_case[1]);
+    /// Returns `Err` with the error atom when guard is interpreted into
+    /// `(Error ...)`.
+    fn guard_passed(&self, guard: &Atom) -> Result<Result<bool, Atom>, ExecError> {
+        let results = interpret_nested(self.space.clone(), guard, &self.options)?;
+        log::debug!("CaseOp::guard_passed: guard: {}, results: {:?}", guard, results);
+        if let Some(error) = results.iter().find(|res| atom_is_error(res)) {
+            return Ok(Err(error.clone()));
+        }
+        Ok(Ok(results.iter().any(|res| bool::from_atom(res) == Some(true) || *res == sym!("True"))))
+    }
+
+    fn first_case_matched(&self, atom: &Atom, cases: &[Case]) -> Result<Vec<Atom>, ExecError> {
+        for case in cases.iter().filter(|case| !case.is_default()) {
+            log::debug!("CaseOp::first_case_matched: next case: {}", case.pattern);
+            let mut result = Vec::new();
+            for bindings in match_atoms(atom, &case.pattern) {
+                let passed = match case.guard {
+                    Some(ref guard) => match self.guard_passed(&apply_bindings_to_atom(guard, &bindings))? {
+                        Ok(passed) => passed,
+                        Err(error) => return Ok(vec![error]),
+                    },
+                    None => true,
+                };
+                if passed {
+                    result.push(apply_bindings_to_atom(&case.template, &bindings));
+                }
+            }
             if !result.is_empty() {
                 return Ok(result)
             }
         }
-        return Ok(vec![])
+        Ok(Self::default_case(cases, &[CASE_ELSE_SYMBOL]))
+    }
+
+    fn default_case(cases: &[Case], patterns: &[Atom]) -> Vec<Atom> {
+        cases.iter().find(|case| patterns.contains(&case.pattern))
+            .map_or(vec![], |case| vec![case.template.clone()])
     }
 }
 
@@ -410,6 +484,181 @@ impl Display for CaseOp {
     }
 }
 
+/// Implements `(throw <reason>)` operation which returns `(Error (throw
+/// <reason>) <reason>)` atom. Error is propagated by interpreter to the
+/// enclosing function calls until it is caught by `catch`. When `<reason>`
+/// is an error already then it is rethrown as is.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ThrowOp {}
+
+impl Display for ThrowOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "throw")
+    }
+}
+
+impl Grounded for ThrowOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_UNDEFINED, ATOM_TYPE_UNDEFINED])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let reason = args.first().ok_or("throw expects one argument: reason of the error")?;
+        if atom_is_error(reason) {
+            Ok(vec![reason.clone()])
+        } else {
+            let call = Atom::expr([Atom::gnd(self.clone()), reason.clone()]);
+            Ok(vec![Atom::expr([ERROR_SYMBOL, call, reason.clone()])])
+        }
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+/// Implements `(catch <atom> <pattern> <handler>)` operation. `<atom>` is
+/// interpreted and each `(Error ...)` result which is matched by
+/// `<pattern>` is replaced by `<handler>` with the bindings of the match
+/// applied. Other results including errors which are not matched are
+/// returned as is, thus successful alternatives are kept.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CatchOp {
+    space: Shared<GroundingSpace>,
+    options: RunnerOptions,
+}
+
+impl CatchOp {
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
+    }
+}
+
+impl Display for CatchOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "catch")
+    }
+}
+
+impl Grounded for CatchOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_ATOM, ATOM_TYPE_ATOM, ATOM_TYPE_UNDEFINED])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let arg_error = || ExecError::from("catch expects three arguments: atom, error pattern and handler");
+        let atom = args.first().ok_or_else(arg_error)?;
+        let pattern = args.get(1).ok_or_else(arg_error)?;
+        let handler = args.get(2).ok_or_else(arg_error)?;
+        // variables of the handler should not clash with the variables of the result
+        let (pattern, handler) = match make_variables_unique(&Atom::expr([pattern.clone(), handler.clone()])) {
+            Atom::Expression(expr) => (expr.children()[0].clone(), expr.children()[1].clone()),
+            _ => unreachable!(),
+        };
+
+        let results = interpret_nested(self.space.clone(), atom, &self.options)?;
+        log::debug!("CatchOp::execute: atom: {}, results: {:?}", atom, results);
+        Ok(results.into_iter().map(|result| {
+            if !atom_is_error(&result) {
+                return result;
+            }
+            match match_atoms(&result, &pattern).next() {
+                Some(bindings) => apply_bindings_to_atom(&handler, &bindings),
+                None => result,
+            }
+        }).collect())
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+/// Head of the `(quote <atom>)` expression. `quote` is declared in
+/// [metta_code] as a function with an `Atom` argument and without rules thus
+/// quoted atom is neither reduced nor evaluated.
+pub const QUOTE_SYMBOL : Atom = sym!("quote");
+
+/// Implements `(eval <atom>)` operation which interprets `<atom>`. It is used
+/// to evaluate expressions which are constructed or passed as `Atom`. When
+/// `<atom>` is quoted then quote is removed before interpretation.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EvalOp {
+    space: Shared<GroundingSpace>,
+    options: RunnerOptions,
+}
+
+impl EvalOp {
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
+    }
+}
+
+impl Display for EvalOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "eval")
+    }
+}
+
+impl Grounded for EvalOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_UNDEFINED])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let atom = args.first().ok_or("eval expects one argument: atom")?;
+        let atom = match atom_as_expr(atom).map(|expr| expr.children().as_slice()) {
+            Some([quote, quoted]) if *quote == QUOTE_SYMBOL => quoted,
+            _ => atom,
+        };
+        Ok(interpret_nested(self.space.clone(), atom, &self.options)?)
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+/// Implements `(unify <atom> <pattern> <then> <else>)` operation. `<atom>`
+/// is matched with `<pattern>` and `<then>` is returned with the bindings
+/// applied for each match found. `<else>` is returned when atoms cannot be
+/// matched.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UnifyOp {}
+
+impl Display for UnifyOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unify")
+    }
+}
+
+impl Grounded for UnifyOp {
+    fn type_(&self) -> Atom {
+        Atom::expr([ARROW_SYMBOL, ATOM_TYPE_ATOM, ATOM_TYPE_ATOM, ATOM_TYPE_ATOM, ATOM_TYPE_ATOM, ATOM_TYPE_UNDEFINED])
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let arg_error = || ExecError::from("unify expects four arguments: atom, pattern, then and else branches");
+        let atom = args.first().ok_or_else(arg_error)?;
+        let pattern = args.get(1).ok_or_else(arg_error)?;
+        let then = args.get(2).ok_or_else(arg_error)?;
+        let else_ = args.get(3).ok_or_else(arg_error)?;
+
+        let result: Vec<Atom> = match_atoms(atom, pattern)
+            .map(|bindings| apply_bindings_to_atom(then, &bindings))
+            .collect();
+        log::debug!("UnifyOp::execute: atom: {}, pattern: {}, result: {:?}", atom, pattern, result);
+        match result.is_empty() {
+            true => Ok(vec![else_.clone()]),
+            false => Ok(result),
+        }
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
 // TODO: move it into hyperon::atom module?
 fn atom_as_expr(atom: &Atom) -> Option<&ExpressionAtom> {
     match atom {
@@ -429,26 +678,22 @@ impl Grounded for CaseOp {
         let cases = atom_as_expr(args.get(1).ok_or_else(arg_error)?).ok_or("case expects expression of cases as a second argument")?;
         log::debug!("CaseOp::execute: atom: {}, cases: {}", atom, cases);
 
-        let result = interpret_no_error(self.space.clone(), atom);
+        let cases = cases.children().iter().map(Case::parse).collect::<Result<Vec<Case>, ExecError>>()?;
+
+        let result = interpret_nested(self.space.clone(), atom, &self.options)?;
         log::debug!("case: interpretation result {:?}", result);
-        match result {
-            Ok(result) if result.is_empty() => {
-                for c in cases.children() {
-                    let next_case = atom_as_expr(c).ok_or("case expects expression of pairs as a second argument")?.children();
-                    if next_case[0] == VOID_SYMBOL {
-                        return Ok(vec![next_case[1].clone()])
-                    }
-                }
-                Ok(vec![])
-            },
-            Ok(result) => {
-                let mut triggered = vec![];
-                for atom in result {
-                    triggered.append(&mut CaseOp::first_case_matched(&atom, cases)?)
+        if result.is_empty() {
+            Ok(Self::default_case(&cases, &[VOID_SYMBOL, CASE_ELSE_SYMBOL]))
+        } else {
+            let mut triggered = vec![];
+            for atom in result {
+                if atom_is_error(&atom) {
+                    triggered.push(atom);
+                } else {
+                    triggered.append(&mut self.first_case_matched(&atom, &cases)?)
                 }
-                Ok(triggered)
-            },
-            Err(message) => Err(format!("Error: {}", message).into()),
+            }
+            Ok(triggered)
         }
     }
 
@@ -469,11 +714,12 @@ fn assert_results_equal(actual: &Vec<Atom>, expected: &Vec<Atom>, atom: &Atom) -
 #[derive(Clone, PartialEq, Debug)]
 pub struct AssertEqualOp {
     space: Shared<GroundingSpace>,
+    options: RunnerOptions,
 }
 
 impl AssertEqualOp {
-    pub fn new(space: Shared<GroundingSpace>) -> Self {
-        Self{ space }
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
     }
 }
 
@@ -493,8 +739,8 @@ impl Grounded for AssertEqualOp {
         let actual_atom = args.get(0).ok_or_else(arg_error)?;
         let expected_atom = args.get(1).ok_or_else(arg_error)?;
 
-        let actual = interpret_no_error(self.space.clone(), actual_atom)?;
-        let expected = interpret_no_error(self.space.clone(), expected_atom)?;
+        let actual = interpret_no_error(self.space.clone(), actual_atom, &self.options)?;
+        let expected = interpret_no_error(self.space.clone(), expected_atom, &self.options)?;
 
         assert_results_equal(&actual, &expected, actual_atom)
     }
@@ -507,11 +753,12 @@ impl Grounded for AssertEqualOp {
 #[derive(Clone, PartialEq, Debug)]
 pub struct AssertEqualToResultOp {
     space: Shared<GroundingSpace>,
+    options: RunnerOptions,
 }
 
 impl AssertEqualToResultOp {
-    pub fn new(space: Shared<GroundingSpace>) -> Self {
-        Self{ space }
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
     }
 }
 
@@ -533,7 +780,7 @@ impl Grounded for AssertEqualToResultOp {
             .ok_or("assertEqualToResult expects expression of results as a second argument")?
             .children();
 
-        let actual = interpret_no_error(self.space.clone(), actual_atom)?;
+        let actual = interpret_no_error(self.space.clone(), actual_atom, &self.options)?;
 
         assert_results_equal(&actual, expected, actual_atom)
     }
@@ -546,11 +793,12 @@ impl Grounded for AssertEqualToResultOp {
 #[derive(Clone, PartialEq, Debug)]
 pub struct CollapseOp {
     space: Shared<GroundingSpace>,
+    options: RunnerOptions,
 }
 
 impl CollapseOp {
-    pub fn new(space: Shared<GroundingSpace>) -> Self {
-        Self{ space }
+    pub fn new(space: Shared<GroundingSpace>, options: RunnerOptions) -> Self {
+        Self{ space, options }
     }
 }
 
@@ -571,7 +819,7 @@ impl Grounded for CollapseOp {
 
         // TODO: Calling interpreter inside the operation is not too good
         // Could it be done via StepResult?
-        let result = interpret_no_error(self.space.clone(), atom)?;
+        let result = interpret_no_error(self.space.clone(), atom, &self.options)?;
 
         Ok(vec![Atom::expr(result)])
     }
@@ -647,6 +895,44 @@ impl Grounded for PragmaOp {
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProfileOp {
+    profiler: Shared<Profiler>,
+}
+
+impl ProfileOp {
+    pub fn new(profiler: Shared<Profiler>) -> Self {
+        Self{ profiler }
+    }
+}
+
+impl Display for ProfileOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "profile!")
+    }
+}
+
+impl Grounded for ProfileOp {
+    fn type_(&self) -> Atom {
+        ATOM_TYPE_UNDEFINED
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        match args.get(0) {
+            None => Ok(vec![self.profiler.borrow().as_atom()]),
+            Some(Atom::Symbol(cmd)) if cmd.name() == "reset" => {
+                self.profiler.borrow_mut().reset();
+                Ok(vec![])
+            },
+            Some(_) => Err("profile! expects no arguments or reset command".into()),
+        }
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct GetTypeOp {
     space: Shared<GroundingSpace>,
@@ -933,6 +1219,11 @@ fn regex(regex: &str) -> Regex {
     Regex::new(regex).unwrap()
 }
 
+/// Wraps `op` to execute it only when `capability` is enabled for `metta`.
+fn restricted(metta: &Metta, capability: Capability, op: Atom) -> Atom {
+    Atom::gnd(RestrictedOp::new(op, capability, metta.capabilities.clone()))
+}
+
 pub fn register_common_tokens(metta: &Metta) {
     let tokenizer = &metta.tokenizer;
     let mut tref = tokenizer.borrow_mut();
@@ -943,9 +1234,9 @@ pub fn register_common_tokens(metta: &Metta) {
     tref.register_token(regex(r"bind!"), move |_| { bind_op.clone() });
     let new_space_op = Atom::gnd(NewSpaceOp{});
     tref.register_token(regex(r"new-space"), move |_| { new_space_op.clone() });
-    let add_atom_op = Atom::gnd(AddAtomOp{});
+    let add_atom_op = restricted(metta, Capability::SpaceMutation, Atom::gnd(AddAtomOp{}));
     tref.register_token(regex(r"add-atom"), move |_| { add_atom_op.clone() });
-    let remove_atom_op = Atom::gnd(RemoveAtomOp{});
+    let remove_atom_op = restricted(metta, Capability::SpaceMutation, Atom::gnd(RemoveAtomOp{}));
     tref.register_token(regex(r"remove-atom"), move |_| { remove_atom_op.clone() });
     let get_atoms_op = Atom::gnd(GetAtomsOp{});
     tref.register_token(regex(r"get-atoms"), move |_| { get_atoms_op.clone() });
@@ -957,7 +1248,7 @@ pub fn register_common_tokens(metta: &Metta) {
     tref.register_token(regex(r"cons-atom"), move |_| { cons_atom_op.clone() });
     let superpose_op = Atom::gnd(SuperposeOp{});
     tref.register_token(regex(r"superpose"), move |_| { superpose_op.clone() });
-    let println_op = Atom::gnd(PrintlnOp{});
+    let println_op = restricted(metta, Capability::Console, Atom::gnd(PrintlnOp{}));
     tref.register_token(regex(r"println!"), move |_| { println_op.clone() });
     let nop_op = Atom::gnd(NopOp{});
     tref.register_token(regex(r"nop"), move |_| { nop_op.clone() });
@@ -967,10 +1258,14 @@ pub fn register_common_tokens(metta: &Metta) {
     tref.register_token(regex(r"let\*"), move |_| { let_var_op.clone() });
     let new_state_op = Atom::gnd(NewStateOp{});
     tref.register_token(regex(r"new-state"), move |_| { new_state_op.clone() });
-    let change_state_op = Atom::gnd(ChangeStateOp{});
+    let change_state_op = restricted(metta, Capability::StateMutation, Atom::gnd(ChangeStateOp{}));
     tref.register_token(regex(r"change-state!"), move |_| { change_state_op.clone() });
     let get_state_op = Atom::gnd(GetStateOp{});
     tref.register_token(regex(r"get-state"), move |_| { get_state_op.clone() });
+    let throw_op = Atom::gnd(ThrowOp{});
+    tref.register_token(regex(r"throw"), move |_| { throw_op.clone() });
+    let unify_op = Atom::gnd(UnifyOp{});
+    tref.register_token(regex(r"unify"), move |_| { unify_op.clone() });
 }
 
 pub fn register_runner_tokens(metta: &Metta, cwd: PathBuf) {
@@ -979,25 +1274,45 @@ pub fn register_runner_tokens(metta: &Metta, cwd: PathBuf) {
 
     let mut tref = tokenizer.borrow_mut();
 
-    let case_op = Atom::gnd(CaseOp::new(space.clone()));
+    let case_op = Atom::gnd(CaseOp::new(space.clone(), metta.runner_options()));
     tref.register_token(regex(r"case"), move |_| { case_op.clone() });
-    let assert_equal_op = Atom::gnd(AssertEqualOp::new(space.clone()));
+    let catch_op = Atom::gnd(CatchOp::new(space.clone(), metta.runner_options()));
+    tref.register_token(regex(r"catch"), move |_| { catch_op.clone() });
+    let eval_op = Atom::gnd(EvalOp::new(space.clone(), metta.runner_options()));
+    tref.register_token(regex(r"eval"), move |_| { eval_op.clone() });
+    let assert_equal_op = Atom::gnd(AssertEqualOp::new(space.clone(), metta.runner_options()));
     tref.register_token(regex(r"assertEqual"), move |_| { assert_equal_op.clone() });
-    let assert_equal_to_result_op = Atom::gnd(AssertEqualToResultOp::new(space.clone()));
+    let assert_equal_to_result_op = Atom::gnd(AssertEqualToResultOp::new(space.clone(), metta.runner_options()));
     tref.register_token(regex(r"assertEqualToResult"), move |_| { assert_equal_to_result_op.clone() });
-    let collapse_op = Atom::gnd(CollapseOp::new(space.clone()));
+    let collapse_op = Atom::gnd(CollapseOp::new(space.clone(), metta.runner_options()));
     tref.register_token(regex(r"collapse"), move |_| { collapse_op.clone() });
     let get_type_op = Atom::gnd(GetTypeOp::new(space.clone()));
     tref.register_token(regex(r"get-type"), move |_| { get_type_op.clone() });
+    let list_map_op = Atom::gnd(ListMapOp::new(space.clone(), metta.runner_options()));
+    tref.register_token(regex(r"list-map"), move |_| { list_map_op.clone() });
+    let list_filter_op = Atom::gnd(ListFilterOp::new(space.clone(), metta.runner_options()));
+    tref.register_token(regex(r"list-filter"), move |_| { list_filter_op.clone() });
+    let list_foldl_op = Atom::gnd(ListFoldlOp::new(space.clone(), metta.runner_options()));
+    tref.register_token(regex(r"list-foldl"), move |_| { list_foldl_op.clone() });
+    for op in aggregate_ops(space.clone(), metta.runner_options()) {
+        tref.register_token(regex(&regex::escape(&op.to_string())), move |_| { op.clone() });
+    }
+    for op in file_ops(Files::new(cwd.clone(), metta.capabilities.clone())) {
+        let op = restricted(metta, Capability::FileSystem, op);
+        tref.register_token(regex(&regex::escape(&op.to_string())), move |_| { op.clone() });
+    }
     // TODO: here clone of the metta is moved into separate location in memory.
     // It means that shared reference inside ImportOp points into a different
     // instance of the Metta struct, not one which is referenced by metta.
     // This can lead to inconsistence when Metta struct is changed and some
     // non-shared field is added to it.
-    let import_op = Atom::gnd(ImportOp::new(Shared::new(metta.clone()), cwd.clone()));
+    let import_op = restricted(metta, Capability::ModuleLoading,
+        Atom::gnd(ImportOp::new(Shared::new(metta.clone()), cwd.clone())));
     tref.register_token(regex(r"import!"), move |_| { import_op.clone() });
     let pragma_op = Atom::gnd(PragmaOp::new(metta.settings.clone()));
     tref.register_token(regex(r"pragma!"), move |_| { pragma_op.clone() });
+    let profile_op = Atom::gnd(ProfileOp::new(metta.profiler.clone()));
+    tref.register_token(regex(r"profile!"), move |_| { profile_op.clone() });
 
     // &self should be updated
     // TODO: adding &self might be done not by stdlib, but by MeTTa itself.
@@ -1014,9 +1329,9 @@ pub fn register_rust_tokens(metta: &Metta) {
     let mut rust_tokens = Tokenizer::new();
     let tref = &mut rust_tokens;
 
-    tref.register_token(regex(r"\d+"),
+    tref.register_token(regex(r"-?(0[xX][0-9a-fA-F](_?[0-9a-fA-F])*|\d(_?\d)*)"),
         |token| { Atom::gnd(Number::from_int_str(token)) });
-    tref.register_token(regex(r"\d+(.\d+)([eE][\-\+]?\d+)?"),
+    tref.register_token(regex(r"-?\d(_?\d)*(\.\d(_?\d)*([eE][\-\+]?\d+)?|[eE][\-\+]?\d+)"),
         |token| { Atom::gnd(Number::from_float_str(token)) });
     tref.register_token(regex(r"True|False"),
         |token| { Atom::gnd(Bool::from_str(token)) });
@@ -1030,6 +1345,27 @@ pub fn register_rust_tokens(metta: &Metta) {
     tref.register_token(regex(r"/"), move |_| { div_op.clone() });
     let mod_op = Atom::gnd(ModOp{});
     tref.register_token(regex(r"%"), move |_| { mod_op.clone() });
+    let less_op = Atom::gnd(LessOp{});
+    tref.register_token(regex(r"<"), move |_| { less_op.clone() });
+    let greater_op = Atom::gnd(GreaterOp{});
+    tref.register_token(regex(r">"), move |_| { greater_op.clone() });
+    let less_eq_op = Atom::gnd(LessEqOp{});
+    tref.register_token(regex(r"<="), move |_| { less_eq_op.clone() });
+    let greater_eq_op = Atom::gnd(GreaterEqOp{});
+    tref.register_token(regex(r">="), move |_| { greater_eq_op.clone() });
+    let equal_op = Atom::gnd(EqualOp{});
+    tref.register_token(regex(r"=="), move |_| { equal_op.clone() });
+    let and_op = Atom::gnd(AndOp{});
+    tref.register_token(regex(r"and"), move |_| { and_op.clone() });
+    let or_op = Atom::gnd(OrOp{});
+    tref.register_token(regex(r"or"), move |_| { or_op.clone() });
+    let not_op = Atom::gnd(NotOp{});
+    tref.register_token(regex(r"not"), move |_| { not_op.clone() });
+    tref.register_token(regex(r#""(?:[^"\\]|\\.)*""#),
+        |token| { Str::from_literal(token).map_or_else(|_| Atom::sym(token), Atom::gnd) });
+    for op in math_ops().into_iter().chain(string_ops()).chain(list_ops()) {
+        tref.register_token(regex(&regex::escape(&op.to_string())), move |_| { op.clone() });
+    }
 
     metta.tokenizer.borrow_mut().move_front(&mut rust_tokens);
 }
@@ -1041,6 +1377,9 @@ pub fn metta_code() -> &'static str {
     (: if (-> Bool Atom Atom $t))
     (= (if True $then $else) $then)
     (= (if False $then $else) $else)
+
+    (: quote (-> Atom Atom))
+    (= (unquote (quote $atom)) $atom)
     "
 }
 
@@ -1144,7 +1483,7 @@ mod tests {
             (= (foo) (A B))
         "));
 
-        let case_op = CaseOp::new(space.clone());
+        let case_op = CaseOp::new(space.clone(), RunnerOptions::default());
 
         assert_eq!(case_op.execute(&mut vec![expr!(("foo")),
                 expr!(((n "B") n) ("%void%" "D"))]),
@@ -1154,6 +1493,92 @@ mod tests {
             Ok(vec![Atom::sym("D")]));
     }
 
+    #[test]
+    fn case_op_guards_and_default() {
+        let space = Shared::new(metta_space("
+            (= (foo) (A B))
+            (= (foo) (A C))
+            (= (allowed C) True)
+            (= (error) (error))
+        "));
+        let case_op = CaseOp::new(space.clone(), RunnerOptions::default());
+
+        assert_eq!(case_op.execute(&mut vec![expr!(("foo")),
+                expr!(((("A" x) ("allowed" x) x)))]),
+            Ok(vec![Atom::sym("C")]));
+        assert_eq_no_order!(case_op.execute(&mut vec![expr!(("foo")),
+                expr!((("A" "B") "first") ("else" "other"))]).unwrap(),
+            vec![Atom::sym("first"), Atom::sym("other")]);
+        assert_eq!(case_op.execute(&mut vec![expr!({MatchOp{}} {space.clone()} ("B" x) x),
+                expr!((("else" "D")))]),
+            Ok(vec![Atom::sym("D")]));
+        assert_eq!(case_op.execute(&mut vec![expr!(("foo")), expr!((("A")))]),
+            Err(ExecError::from("case expects (pattern template) or (pattern guard template) as a case")));
+    }
+
+    #[test]
+    fn case_op_propagates_errors() {
+        let space = Shared::new(metta_space("
+            (= (foo) (A B))
+            (= (foo) (Error (foo) Failed))
+            (= (check B) (Error (check B) Failed))
+        "));
+        let case_op = CaseOp::new(space, RunnerOptions::default());
+
+        assert_eq_no_order!(case_op.execute(&mut vec![expr!(("foo")), expr!((("else" "other")))]).unwrap(),
+            vec![sym!("other"), expr!("Error" ("foo") "Failed")]);
+        assert_eq!(case_op.execute(&mut vec![expr!("A" "B"), expr!((("A" x) ("check" x) "found") ("else" "other"))]),
+            Ok(vec![expr!("Error" ("check" "B") "Failed")]));
+    }
+
+    #[test]
+    fn throw_op() {
+        let throw = Atom::gnd(ThrowOp{});
+        assert_eq!(ThrowOp{}.execute(&mut vec![sym!("Oops")]),
+            Ok(vec![Atom::expr([ERROR_SYMBOL, Atom::expr([throw, sym!("Oops")]), sym!("Oops")])]));
+        assert_eq!(ThrowOp{}.execute(&mut vec![expr!("Error" "a" "Oops")]),
+            Ok(vec![expr!("Error" "a" "Oops")]));
+    }
+
+    #[test]
+    fn catch_op() {
+        let space = Shared::new(metta_space("
+            (= (foo) ok)
+            (= (foo) (Error foo Oops))
+            (= (bar) (Error bar Other))
+        "));
+        let catch_op = CatchOp::new(space.clone(), RunnerOptions::default());
+
+        assert_eq_no_order!(catch_op.execute(&mut vec![expr!(("foo")), expr!("Error" a "Oops"), expr!("caught" a)]).unwrap(),
+            vec![sym!("ok"), expr!("caught" "foo")]);
+        assert_eq!(catch_op.execute(&mut vec![expr!(("bar")), expr!("Error" a "Oops"), expr!("caught" a)]),
+            Ok(vec![expr!("Error" "bar" "Other")]));
+        assert_eq!(catch_op.execute(&mut vec![expr!(("bar"))]),
+            Err(ExecError::from("catch expects three arguments: atom, error pattern and handler")));
+    }
+
+    #[test]
+    fn eval_op() {
+        let space = Shared::new(metta_space("
+            (= (foo) bar)
+        "));
+        let eval_op = EvalOp::new(space, RunnerOptions::default());
+
+        assert_eq!(eval_op.execute(&mut vec![expr!(("foo"))]), Ok(vec![sym!("bar")]));
+        assert_eq!(eval_op.execute(&mut vec![expr!("quote" ("foo"))]), Ok(vec![sym!("bar")]));
+        assert_eq!(eval_op.execute(&mut vec![expr!("quote" "a" "b")]), Ok(vec![expr!("quote" "a" "b")]));
+    }
+
+    #[test]
+    fn unify_op() {
+        assert_eq!(UnifyOp{}.execute(&mut vec![expr!("A" "B"), expr!("A" x), expr!("ok" x), sym!("no")]),
+            Ok(vec![expr!("ok" "B")]));
+        assert_eq!(UnifyOp{}.execute(&mut vec![expr!("A" "B"), expr!("B" x), expr!("ok" x), sym!("no")]),
+            Ok(vec![sym!("no")]));
+        assert_eq!(UnifyOp{}.execute(&mut vec![expr!("A" x), expr!(y "B"), expr!(x y), sym!("no")]),
+            Ok(vec![expr!("B" "A")]));
+    }
+
     fn assert_runtime_error(actual: Result<Vec<Atom>, ExecError>, expected: Regex) {
         match actual {
             Err(ExecError::Runtime(msg)) => assert!(expected.is_match(msg.as_str()),
@@ -1172,7 +1597,7 @@ mod tests {
             (= (err) (A B))
         "));
 
-        let assert_equal_op = AssertEqualOp::new(space);
+        let assert_equal_op = AssertEqualOp::new(space, RunnerOptions::default());
 
         assert_eq!(assert_equal_op.execute(&mut vec![expr!(("foo")), expr!(("bar"))]), Ok(vec![]));
 
@@ -1191,7 +1616,7 @@ mod tests {
             (= (foo) (A B))
             (= (foo) (B C))
         "));
-        let assert_equal_to_result_op = AssertEqualToResultOp::new(space);
+        let assert_equal_to_result_op = AssertEqualToResultOp::new(space, RunnerOptions::default());
 
         assert_eq!(assert_equal_to_result_op.execute(&mut vec![
                 expr!(("foo")), expr!(("B" "C") ("A" "B"))]),
@@ -1204,7 +1629,7 @@ mod tests {
             (= (foo) (A B))
             (= (foo) (B C))
         "));
-        let collapse_op = CollapseOp::new(space);
+        let collapse_op = CollapseOp::new(space, RunnerOptions::default());
 
         let actual = collapse_op.execute(&mut vec![expr!(("foo"))]).unwrap();
         assert_eq!(actual.len(), 1);
@@ -1234,6 +1659,19 @@ mod tests {
             vec![sym!("B"), sym!("C")]);
     }
 
+    #[test]
+    fn profile_op() {
+        let profiler = Shared::new(Profiler::new());
+        profiler.borrow_mut().add_execute(&expr!("foo"), std::time::Duration::from_micros(3));
+        let profile_op = ProfileOp::new(profiler.clone());
+
+        assert_eq!(profile_op.execute(&mut vec![]), Ok(vec![expr!("Profile"
+            ("Functions") ("GroundedOps" ("foo" ("Calls" "1") ("ExecuteTimeUs" "3"))))]));
+        assert_eq!(profile_op.execute(&mut vec![sym!("reset")]), Ok(vec![]));
+        assert_eq!(profile_op.execute(&mut vec![]),
+            Ok(vec![expr!("Profile" ("Functions") ("GroundedOps"))]));
+    }
+
     #[test]
     fn println_op() {
         assert_eq!(PrintlnOp{}.execute(&mut vec![sym!("A")]), Ok(vec![]));
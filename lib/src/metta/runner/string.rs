@@ -0,0 +1,235 @@
+use crate::*;
+use super::arithmetics::Number;
+use super::typed::{GroundedFn, GroundedValue};
+
+use std::fmt::Display;
+
+pub const ATOM_TYPE_STRING : Atom = sym!("String");
+
+/// Grounded string. Literal is written in double quotes and supports
+/// `\n`, `\r`, `\t`, `\0`, `\\`, `\"`, `\'` and `\u{XXXX}` escape sequences.
+/// Literal with incorrect escape sequence is parsed as a symbol.
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(type = ATOM_TYPE_STRING)]
+pub struct Str(String);
+
+impl Str {
+    pub fn from_string(s: String) -> Self {
+        Self(s)
+    }
+
+    /// Parses string literal including surrounding double quotes, returns
+    /// error when literal is not quoted or contains incorrect escape sequence.
+    pub fn from_literal(literal: &str) -> Result<Self, String> {
+        let text = literal.strip_prefix('"').and_then(|s| s.strip_suffix('"'))
+            .ok_or_else(|| format!("String literal should be in double quotes: {}", literal))?;
+        unescape(text).map(Self).map_err(|msg| format!("{}: {}", msg, literal))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let c = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '"' | '\'')) => c,
+            Some('u') => {
+                let code: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                code.strip_prefix('{')
+                    .and_then(|code| u32::from_str_radix(code, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("Incorrect unicode escape sequence \\u{}}}", code))?
+            },
+            Some(c) => return Err(format!("Unknown escape sequence \\{}", c)),
+            None => return Err("Escape sequence is not finished".into()),
+        };
+        result.push(c);
+    }
+    Ok(result)
+}
+
+impl Display for Str {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl GroundedValue for String {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_STRING
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        atom.as_gnd::<Str>().map(|s| s.0.clone())
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::gnd(Str(self))
+    }
+}
+
+fn strings(atoms: &[Atom], op: &str) -> Result<Vec<String>, ExecError> {
+    atoms.iter().map(|atom| String::from_atom(atom)
+        .ok_or_else(|| ExecError::from(format!("{} expects expression of strings, got {}", op, atom))))
+        .collect()
+}
+
+/// Returns string which is used to represent atom in [str_format].
+fn format_arg(atom: &Atom) -> String {
+    match atom.as_gnd::<Str>() {
+        Some(s) => s.0.clone(),
+        None => atom.to_string(),
+    }
+}
+
+/// Replaces each `{}` in `format` by the next argument, `{{` and `}}` are
+/// used to insert braces.
+fn str_format(format: String, args: Vec<Atom>) -> Result<String, ExecError> {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                result.push(c);
+                chars.next();
+            },
+            ('{', Some('}')) => {
+                let arg = args.next().ok_or("str-format: not enough arguments")?;
+                result.push_str(&format_arg(arg));
+                chars.next();
+            },
+            ('{', _) | ('}', _) => return Err("str-format: unmatched brace in format string".into()),
+            _ => result.push(c),
+        }
+    }
+    match args.next() {
+        Some(_) => Err("str-format: too many arguments".into()),
+        None => Ok(result),
+    }
+}
+
+fn substring(s: String, start: i64, end: i64) -> Result<String, ExecError> {
+    let len = s.chars().count() as i64;
+    if start < 0 || start > end || end > len {
+        return Err(format!("str-substring: incorrect range [{}, {}) for string of length {}", start, end, len).into());
+    }
+    Ok(s.chars().skip(start as usize).take((end - start) as usize).collect())
+}
+
+fn parse_number(s: String) -> Result<Number, ExecError> {
+    let s = s.trim();
+    Number::parse_int(s)
+        .or_else(|| s.parse::<f64>().ok().map(Number::Float))
+        .ok_or_else(|| ExecError::from(format!("str-to-number: cannot parse number from {:?}", s)))
+}
+
+/// Returns string operations, the name of each operation is its token.
+pub fn string_ops() -> Vec<Atom> {
+    vec![
+        Atom::gnd(GroundedFn::new("str-concat", |a: String, b: String| a + &b)),
+        Atom::gnd(GroundedFn::new("str-length", |s: String| s.chars().count() as i64)),
+        Atom::gnd(GroundedFn::new("str-substring", substring)),
+        Atom::gnd(GroundedFn::new("str-split", |s: String, sep: String| {
+            if sep.is_empty() {
+                return Err(ExecError::from("str-split: separator is empty"));
+            }
+            Ok(s.split(sep.as_str()).map(|part| String::from(part).into_atom()).collect::<Vec<Atom>>())
+        })),
+        Atom::gnd(GroundedFn::new("str-join", |sep: String, parts: Vec<Atom>| {
+            strings(&parts, "str-join").map(|parts| parts.join(&sep))
+        })),
+        Atom::gnd(GroundedFn::new("str-format", str_format)),
+        Atom::gnd(GroundedFn::new("str-to-symbol", |s: String| {
+            match s.is_empty() || s.contains(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+                true => Err(ExecError::from(format!("str-to-symbol: {:?} is not a valid symbol name", s))),
+                false => Ok(Atom::sym(s)),
+            }
+        })),
+        Atom::gnd(GroundedFn::new("symbol-to-str", |sym: SymbolAtom| sym.name().to_string())),
+        Atom::gnd(GroundedFn::new("str-to-number", parse_number)),
+        Atom::gnd(GroundedFn::new("number-to-str", |n: Number| n.to_string())),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str_atom(s: &str) -> Atom {
+        Atom::gnd(Str::from_string(s.into()))
+    }
+
+    fn op(name: &str) -> Atom {
+        string_ops().into_iter().find(|op| op.to_string() == name).unwrap()
+    }
+
+    fn execute(name: &str, mut args: Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        match op(name) {
+            Atom::Grounded(gnd) => gnd.execute(&mut args),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn str_literal() {
+        assert_eq!(Str::from_literal(r#""a\"b\\c\n\u{1F600}\u{e9}""#), Ok(Str::from_string("a\"b\\c\n\u{1F600}\u{e9}".into())));
+        assert_eq!(Str::from_literal(r#""""#), Ok(Str::from_string(String::new())));
+        assert_eq!(Str::from_literal(r#""\q""#), Err(r#"Unknown escape sequence \q: "\q""#.into()));
+        assert!(Str::from_literal(r#""\u{110000}""#).is_err());
+        assert!(Str::from_literal("a").is_err());
+        assert_eq!(str_atom("a\"b\n").to_string(), r#""a\"b\n""#);
+        assert_eq!(unescape(r"\q"), Err("Unknown escape sequence \\q".into()));
+        assert_eq!(unescape(r"\u{110000}"), Err("Incorrect unicode escape sequence \\u{110000}".into()));
+        assert_eq!(unescape("a\\"), Err("Escape sequence is not finished".into()));
+    }
+
+    #[test]
+    fn str_ops() {
+        assert_eq!(execute("str-concat", vec![str_atom("ab"), str_atom("c")]), Ok(vec![str_atom("abc")]));
+        assert_eq!(execute("str-length", vec![str_atom("h\u{e9}llo")]), Ok(vec![Atom::gnd(Number::Integer(5))]));
+        assert_eq!(execute("str-substring", vec![str_atom("h\u{e9}llo"), Atom::gnd(Number::Integer(1)), Atom::gnd(Number::Integer(3))]),
+            Ok(vec![str_atom("\u{e9}l")]));
+        assert!(execute("str-substring", vec![str_atom("abc"), Atom::gnd(Number::Integer(2)), Atom::gnd(Number::Integer(4))]).is_err());
+        assert_eq!(execute("str-split", vec![str_atom("a,b,,c"), str_atom(",")]),
+            Ok(vec![Atom::expr([str_atom("a"), str_atom("b"), str_atom(""), str_atom("c")])]));
+        assert_eq!(execute("str-join", vec![str_atom(", "), Atom::expr([str_atom("a"), str_atom("b")])]),
+            Ok(vec![str_atom("a, b")]));
+        assert_eq!(execute("str-join", vec![str_atom(", "), expr!(("a"))]),
+            Err(ExecError::from("str-join expects expression of strings, got a")));
+    }
+
+    #[test]
+    fn str_format_op() {
+        assert_eq!(execute("str-format", vec![str_atom("{} + {} = {{{}}}"),
+                Atom::expr([str_atom("one"), Atom::gnd(Number::Integer(2)), expr!("a" "b")])]),
+            Ok(vec![str_atom("one + 2 = {(a b)}")]));
+        assert_eq!(execute("str-format", vec![str_atom("{}"), expr!()]),
+            Err(ExecError::from("str-format: not enough arguments")));
+        assert_eq!(execute("str-format", vec![str_atom("x"), expr!(("a"))]),
+            Err(ExecError::from("str-format: too many arguments")));
+    }
+
+    #[test]
+    fn str_conversion_ops() {
+        assert_eq!(execute("str-to-symbol", vec![str_atom("foo")]), Ok(vec![sym!("foo")]));
+        assert!(execute("str-to-symbol", vec![str_atom("foo bar")]).is_err());
+        assert_eq!(execute("symbol-to-str", vec![sym!("foo")]), Ok(vec![str_atom("foo")]));
+        assert_eq!(execute("str-to-number", vec![str_atom(" 42 ")]), Ok(vec![Atom::gnd(Number::Integer(42))]));
+        assert_eq!(execute("str-to-number", vec![str_atom("4.5")]), Ok(vec![Atom::gnd(Number::Float(4.5))]));
+        assert!(execute("str-to-number", vec![str_atom("x")]).is_err());
+        assert_eq!(execute("number-to-str", vec![Atom::gnd(Number::Float(4.5))]), Ok(vec![str_atom("4.5")]));
+    }
+}
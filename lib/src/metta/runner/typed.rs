@@ -0,0 +1,295 @@
+//! Helpers to define grounded operations using plain Rust functions. Type of
+//! the operation is derived from the Rust types of the function arguments and
+//! result using [GroundedValue] mapping, arguments are converted before
+//! calling the function.
+//!
+//! # Examples
+//!
+//! ```
+//! use hyperon::*;
+//! use hyperon::metta::runner::typed::{GroundedFn, GroundedValue};
+//!
+//! let add = GroundedFn::new("add", |a: i64, b: i64| a + b);
+//!
+//! assert_eq!(add.type_(), expr!("->" "Number" "Number" "Number"));
+//! assert_eq!(add.execute(&mut vec![1i64.into_atom(), 2i64.into_atom()]), Ok(vec![3i64.into_atom()]));
+//! assert_eq!(add.execute(&mut vec![1i64.into_atom(), sym!("b")]),
+//!     Err(ExecError::from("add expects argument 2 of type Number, got b")));
+//! ```
+
+use crate::*;
+use crate::matcher::MatchResultIter;
+use crate::metta::*;
+
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Rust type which is mapped to the MeTTa type and can be converted from and
+/// into [Atom].
+pub trait GroundedValue: Sized {
+    /// Returns MeTTa type of the Rust type values.
+    fn type_atom() -> Atom;
+    /// Converts `atom` into the Rust value, returns `None` when `atom` has
+    /// another type.
+    fn from_atom(atom: &Atom) -> Option<Self>;
+    /// Converts Rust value into [Atom].
+    fn into_atom(self) -> Atom;
+}
+
+impl GroundedValue for Atom {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_ATOM
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        Some(atom.clone())
+    }
+
+    fn into_atom(self) -> Atom {
+        self
+    }
+}
+
+impl GroundedValue for SymbolAtom {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_SYMBOL
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        match atom {
+            Atom::Symbol(sym) => Some(sym.clone()),
+            _ => None,
+        }
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::Symbol(self)
+    }
+}
+
+/// Children of the expression atom.
+impl GroundedValue for Vec<Atom> {
+    fn type_atom() -> Atom {
+        ATOM_TYPE_EXPRESSION
+    }
+
+    fn from_atom(atom: &Atom) -> Option<Self> {
+        match atom {
+            Atom::Expression(expr) => Some(expr.children().clone()),
+            _ => None,
+        }
+    }
+
+    fn into_atom(self) -> Atom {
+        Atom::expr(self)
+    }
+}
+
+/// Result of the Rust function which can be returned by grounded operation.
+/// Implemented for the [GroundedValue] types and for `Result<T, ExecError>`
+/// where `T` is [GroundedValue].
+pub trait GroundedResult {
+    /// Returns MeTTa type of the result.
+    fn type_atom() -> Atom;
+    /// Converts result into the result of the [Grounded::execute] call.
+    fn into_results(self) -> Result<Vec<Atom>, ExecError>;
+}
+
+impl<T: GroundedValue> GroundedResult for T {
+    fn type_atom() -> Atom {
+        T::type_atom()
+    }
+
+    fn into_results(self) -> Result<Vec<Atom>, ExecError> {
+        Ok(vec![self.into_atom()])
+    }
+}
+
+impl<T: GroundedValue> GroundedResult for Result<T, ExecError> {
+    fn type_atom() -> Atom {
+        T::type_atom()
+    }
+
+    fn into_results(self) -> Result<Vec<Atom>, ExecError> {
+        self.map(|value| vec![value.into_atom()])
+    }
+}
+
+/// Error of the grounded operation arguments conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgError {
+    /// Number of arguments passed is not equal to the number of arguments
+    /// expected.
+    IncorrectNumber{ expected: usize, actual: usize },
+    /// Argument cannot be converted to the expected type, `index` starts
+    /// from zero.
+    BadType{ index: usize, expected: Atom, actual: Atom },
+}
+
+impl Display for ArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::IncorrectNumber{ expected, actual } =>
+                write!(f, "expects {} arguments, {} given", expected, actual),
+            Self::BadType{ index, expected, actual } =>
+                write!(f, "expects argument {} of type {}, got {}", index + 1, expected, actual),
+        }
+    }
+}
+
+/// Converts argument with the `index` into the Rust value of type `T`.
+pub fn arg<T: GroundedValue>(args: &[Atom], index: usize) -> Result<T, ArgError> {
+    let atom = args.get(index).ok_or(ArgError::IncorrectNumber{ expected: index + 1, actual: args.len() })?;
+    T::from_atom(atom).ok_or_else(|| ArgError::BadType{ index, expected: T::type_atom(), actual: atom.clone() })
+}
+
+/// Rust function which can be wrapped by [GroundedFn]. Implemented for the
+/// functions and closures with up to four [GroundedValue] arguments returning
+/// [GroundedResult]. `Args` is a tuple of the argument types.
+pub trait TypedFn<Args> {
+    /// Returns MeTTa type of the function.
+    fn type_atom() -> Atom;
+    /// Converts arguments into the tuple of the Rust values.
+    fn convert_args(args: &[Atom]) -> Result<Args, ArgError>;
+    /// Calls function on the converted arguments.
+    fn apply(&self, args: Args) -> Result<Vec<Atom>, ExecError>;
+}
+
+macro_rules! impl_typed_fn {
+    ($($arg:ident $idx:tt),*) => {
+        impl<F, R, $($arg),*> TypedFn<($($arg,)*)> for F
+            where F: Fn($($arg),*) -> R, R: GroundedResult, $($arg: GroundedValue),*
+        {
+            fn type_atom() -> Atom {
+                Atom::expr([ARROW_SYMBOL, $($arg::type_atom(),)* R::type_atom()])
+            }
+
+            #[allow(unused_variables)]
+            fn convert_args(args: &[Atom]) -> Result<($($arg,)*), ArgError> {
+                let expected = <[&str]>::len(&[$(stringify!($arg)),*]);
+                if args.len() != expected {
+                    return Err(ArgError::IncorrectNumber{ expected, actual: args.len() });
+                }
+                Ok(($(arg::<$arg>(args, $idx)?,)*))
+            }
+
+            #[allow(unused_variables)]
+            fn apply(&self, args: ($($arg,)*)) -> Result<Vec<Atom>, ExecError> {
+                self($(args.$idx),*).into_results()
+            }
+        }
+    }
+}
+
+impl_typed_fn!();
+impl_typed_fn!(A 0);
+impl_typed_fn!(A 0, B 1);
+impl_typed_fn!(A 0, B 1, C 2);
+impl_typed_fn!(A 0, B 1, C 2, D 3);
+
+/// Grounded operation which calls the wrapped Rust function. Two instances
+/// are equal when they wrap the same function instance.
+pub struct GroundedFn<Args, F> {
+    name: String,
+    func: Rc<F>,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl<Args, F: TypedFn<Args>> GroundedFn<Args, F> {
+    /// Wraps `func` into the grounded operation with the given `name`.
+    pub fn new(name: &str, func: F) -> Self {
+        Self{ name: name.into(), func: Rc::new(func), _args: PhantomData }
+    }
+}
+
+impl<Args, F> Clone for GroundedFn<Args, F> {
+    fn clone(&self) -> Self {
+        Self{ name: self.name.clone(), func: self.func.clone(), _args: PhantomData }
+    }
+}
+
+impl<Args, F> PartialEq for GroundedFn<Args, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && Rc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl<Args, F> Debug for GroundedFn<Args, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "GroundedFn({})", self.name)
+    }
+}
+
+impl<Args, F> Display for GroundedFn<Args, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl<Args: 'static, F: TypedFn<Args> + 'static> Grounded for GroundedFn<Args, F> {
+    fn type_(&self) -> Atom {
+        F::type_atom()
+    }
+
+    fn execute(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        let args = F::convert_args(args)
+            .map_err(|err| ExecError::from(format!("{} {}", self.name, err)))?;
+        self.func.apply(args)
+    }
+
+    fn match_(&self, other: &Atom) -> MatchResultIter {
+        match_by_equality(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metta::runner::arithmetics::{Number, ATOM_TYPE_NUMBER, ATOM_TYPE_BOOL};
+    use crate::metta::interpreter::interpret;
+    use crate::space::grounding::GroundingSpace;
+
+    #[test]
+    fn grounded_fn_type() {
+        assert_eq!(GroundedFn::new("c", || 42i64).type_(), expr!("->" "Number"));
+        assert_eq!(GroundedFn::new("not", |a: bool| !a).type_(),
+            Atom::expr([ARROW_SYMBOL, ATOM_TYPE_BOOL, ATOM_TYPE_BOOL]));
+        assert_eq!(GroundedFn::new("quote", |a: Atom| a).type_(), expr!("->" "Atom" "Atom"));
+        assert_eq!(GroundedFn::new("lt", |a: f64, b: Number| Ok(Number::Float(a) == b)).type_(),
+            Atom::expr([ARROW_SYMBOL, ATOM_TYPE_NUMBER, ATOM_TYPE_NUMBER, ATOM_TYPE_BOOL]));
+    }
+
+    #[test]
+    fn grounded_fn_converts_args() {
+        let div = GroundedFn::new("div", |a: i64, b: i64| {
+            if b == 0 { Err(ExecError::from("Division by zero")) } else { Ok(a / b) }
+        });
+
+        assert_eq!(div.execute(&mut vec![Atom::gnd(Number::Integer(6)), Atom::gnd(Number::Integer(3))]),
+            Ok(vec![Atom::gnd(Number::Integer(2))]));
+        assert_eq!(div.execute(&mut vec![Atom::gnd(Number::Integer(6)), Atom::gnd(Number::Integer(0))]),
+            Err(ExecError::from("Division by zero")));
+        assert_eq!(div.execute(&mut vec![Atom::gnd(Number::Integer(6))]),
+            Err(ExecError::from("div expects 2 arguments, 1 given")));
+        assert_eq!(div.execute(&mut vec![Atom::gnd(Number::Float(6.0)), Atom::gnd(Number::Integer(3))]),
+            Err(ExecError::from("div expects argument 1 of type Number, got 6")));
+        assert_eq!(arg::<f64>(&[Atom::gnd(Number::Integer(6))], 0), Ok(6.0));
+        assert_eq!(arg::<bool>(&[sym!("a")], 0),
+            Err(ArgError::BadType{ index: 0, expected: ATOM_TYPE_BOOL, actual: sym!("a") }));
+    }
+
+    #[test]
+    fn grounded_fn_interpret() {
+        let add = Atom::gnd(GroundedFn::new("add", |a: i64, b: i64| a + b));
+        let space = GroundingSpace::new();
+        let expr = Atom::expr([add.clone(), Atom::gnd(Number::Integer(40)), Atom::gnd(Number::Integer(2))]);
+
+        assert_eq!(add, add.clone());
+        assert_ne!(add, Atom::gnd(GroundedFn::new("add", |a: i64, b: i64| a + b)));
+        assert_eq!(interpret(&space, &expr), Ok(vec![Atom::gnd(Number::Integer(42))]));
+        let expr = Atom::expr([add, Atom::gnd(Number::Integer(40)), sym!("b")]);
+        assert_eq!(interpret(&space, &expr), Ok(vec![Atom::expr([ERROR_SYMBOL, expr.clone(),
+            Atom::sym("add expects argument 2 of type Number, got b")])]));
+    }
+}
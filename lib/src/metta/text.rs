@@ -142,24 +142,24 @@ fn next_string(it: &mut Peekable<Chars<'_>>) -> String {
     let mut token = String::new();
     assert_eq!(Some('"'), it.next(), "Double quote expected");
     token.push('"');
-    while let Some(&c) = it.peek() {
-        if c == '"' {
-            token.push('"');
-            it.next();
-            break;
+    loop {
+        match it.next() {
+            Some('"') => {
+                token.push('"');
+                break;
+            },
+            Some('\\') => {
+                token.push('\\');
+                if let Some(c) = it.next() {
+                    token.push(c);
+                }
+            },
+            Some(c) => token.push(c),
+            // unterminated string literal is returned as is and parsed as symbol
+            None => break,
         }
-        let c = if c == '\\' {
-            match it.peek() {
-                Some(&c) => c,
-                None => panic!("Escaping sequence is not finished"),
-            }
-        } else {
-            c
-        };
-        token.push(c);
-        it.next();
     }
-    token 
+    token
 }
 
 fn next_word(it: &mut Peekable<Chars<'_>>) -> String {
@@ -208,6 +208,17 @@ mod tests {
         assert_eq!(vec![expr!("\"te st\"")], parse_atoms("\"te st\""));
     }
 
+    #[test]
+    fn test_text_quoted_string_with_escapes() {
+        assert_eq!(vec![expr!(r#""a \"b\" \\""#), expr!("c")], parse_atoms(r#""a \"b\" \\" c"#));
+    }
+
+    #[test]
+    fn test_text_unterminated_string() {
+        assert_eq!(parse_atoms("\"abc"), vec![sym!("\"abc")]);
+        assert_eq!(parse_atoms("\"abc\\"), vec![sym!("\"abc\\")]);
+    }
+
     #[test]
     fn test_text_recognize_full_token() {
         let mut tokenizer = Tokenizer::new();
@@ -0,0 +1,296 @@
+//! Index of the `:` and `:<` declarations which speeds up type checking.
+//! Type checking functions from [super::types] query the space for the
+//! types and super types of each symbol they meet, see for example
+//! [super::types::get_atom_types]. [TypeIndex] is a [Space] which answers
+//! such queries using the index and passes all other queries to the
+//! underlying space. The index is updated incrementally using
+//! [SpaceObserver] events of the space and of the grounding spaces which
+//! are added into it (for example module spaces loaded by the runner).
+//!
+//! The index falls back to the space queries while the space contains
+//! declarations which can match any symbol, for example `(: $x A)`, or
+//! grounded atoms which are not grounding spaces and may implement custom
+//! matching.
+
+use crate::*;
+use crate::atom::matcher::Bindings;
+use crate::common::shared::Shared;
+use crate::space::{Space, SpaceObserver, SpaceEvent};
+use crate::space::grounding::GroundingSpace;
+use super::*;
+
+use std::rc::{Rc, Weak};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[derive(Default)]
+struct Declarations(HashMap<SymbolAtom, Vec<Atom>>);
+
+impl Declarations {
+    fn add(&mut self, sym: &SymbolAtom, typ: &Atom) {
+        self.0.entry(sym.clone()).or_default().push(typ.clone());
+    }
+
+    fn remove(&mut self, sym: &SymbolAtom, typ: &Atom) {
+        if let Some(types) = self.0.get_mut(sym) {
+            if let Some(i) = types.iter().position(|t| t == typ) {
+                types.remove(i);
+            }
+            if types.is_empty() {
+                self.0.remove(sym);
+            }
+        }
+    }
+
+    fn get(&self, sym: &SymbolAtom) -> &[Atom] {
+        self.0.get(sym).map_or(&[], Vec::as_slice)
+    }
+}
+
+struct ObservedSpace {
+    space: Shared<GroundingSpace>,
+    count: usize,
+    _observer: Rc<RefCell<dyn SpaceObserver>>,
+}
+
+#[derive(Default)]
+struct IndexData {
+    this: Weak<RefCell<IndexData>>,
+    types: Declarations,
+    super_types: Declarations,
+    /// Number of atoms which prevent using the index, see module
+    /// description.
+    unindexed: usize,
+    spaces: Vec<ObservedSpace>,
+}
+
+impl IndexData {
+    fn update(&mut self, atom: &Atom, add: bool) {
+        match atom {
+            Atom::Expression(expr) => match expr.children().as_slice() {
+                [op, sub, typ] if *op == HAS_TYPE_SYMBOL || *op == SUB_TYPE_SYMBOL => {
+                    let declarations = if *op == HAS_TYPE_SYMBOL {
+                        &mut self.types
+                    } else {
+                        &mut self.super_types
+                    };
+                    match sub {
+                        Atom::Symbol(sym) if add => declarations.add(sym, typ),
+                        Atom::Symbol(sym) => declarations.remove(sym, typ),
+                        // expression never matches the symbol
+                        Atom::Expression(_) => {},
+                        _ if add => self.unindexed += 1,
+                        _ => self.unindexed -= 1,
+                    }
+                },
+                _ => {},
+            },
+            Atom::Grounded(_) => match atom.as_gnd::<Shared<GroundingSpace>>() {
+                Some(space) if add => self.attach(space),
+                Some(space) => self.detach(space),
+                None if add => self.unindexed += 1,
+                None => self.unindexed -= 1,
+            },
+            _ => {},
+        }
+    }
+
+    fn attach(&mut self, space: &Shared<GroundingSpace>) {
+        if let Some(observed) = self.spaces.iter_mut().find(|observed| observed.space == *space) {
+            // space which is included twice or includes itself is not indexed
+            observed.count += 1;
+            self.unindexed += 1;
+            return;
+        }
+        let observer = Rc::new(RefCell::new(IndexObserver(self.this.clone())));
+        space.borrow().register_observer(observer.clone());
+        self.spaces.push(ObservedSpace{ space: space.clone(), count: 1, _observer: observer });
+        let atoms: Vec<Atom> = space.borrow().iter().cloned().collect();
+        atoms.iter().for_each(|atom| self.update(atom, true));
+    }
+
+    fn detach(&mut self, space: &Shared<GroundingSpace>) {
+        let i = self.spaces.iter().position(|observed| observed.space == *space)
+            .expect("Space is expected to be observed");
+        if self.spaces[i].count > 1 {
+            self.spaces[i].count -= 1;
+            self.unindexed -= 1;
+            return;
+        }
+        self.spaces.remove(i);
+        let atoms: Vec<Atom> = space.borrow().iter().cloned().collect();
+        atoms.iter().for_each(|atom| self.update(atom, false));
+    }
+}
+
+struct IndexObserver(Weak<RefCell<IndexData>>);
+
+impl SpaceObserver for IndexObserver {
+    fn notify(&mut self, event: &SpaceEvent) {
+        if let Some(data) = self.0.upgrade() {
+            let mut data = data.borrow_mut();
+            match event {
+                SpaceEvent::Add(atom) => data.update(atom, true),
+                SpaceEvent::Remove(atom) => data.update(atom, false),
+                SpaceEvent::Replace(from, to) => {
+                    data.update(from, false);
+                    data.update(to, true);
+                },
+            }
+        }
+    }
+}
+
+/// [Space] which answers type queries using the index of the `:` and `:<`
+/// declarations of the underlying grounding space.
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::{expr, sym};
+/// use hyperon::common::shared::Shared;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::type_index::TypeIndex;
+/// use hyperon::metta::types::{get_atom_types, check_type};
+///
+/// let space = Shared::new(metta_space("(: a A) (:< A B)"));
+/// let index = TypeIndex::new(space.clone());
+///
+/// assert_eq!(get_atom_types(&index, &sym!("a")), vec![sym!("A"), sym!("B")]);
+/// space.borrow_mut().add(expr!(":<" "B" "C"));
+/// assert!(check_type(&index, &sym!("a"), &sym!("C")));
+/// ```
+#[derive(Clone)]
+pub struct TypeIndex {
+    space: Shared<GroundingSpace>,
+    data: Rc<RefCell<IndexData>>,
+}
+
+impl TypeIndex {
+    /// Indexes the `space` and starts tracking its modifications.
+    pub fn new(space: Shared<GroundingSpace>) -> Self {
+        let data = Rc::new(RefCell::new(IndexData::default()));
+        data.borrow_mut().this = Rc::downgrade(&data);
+        data.borrow_mut().attach(&space);
+        Self{ space, data }
+    }
+
+    /// Returns the indexed space.
+    pub fn space(&self) -> Shared<GroundingSpace> {
+        self.space.clone()
+    }
+
+    fn query_index(&self, query: &Atom) -> Option<Vec<Bindings>> {
+        let (op, sym, var) = match query {
+            Atom::Expression(expr) => match expr.children().as_slice() {
+                [op, Atom::Symbol(sym), Atom::Variable(var)] => (op, sym, var),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let data = self.data.borrow();
+        let declarations = if *op == HAS_TYPE_SYMBOL && data.unindexed == 0 {
+            &data.types
+        } else if *op == SUB_TYPE_SYMBOL && data.unindexed == 0 {
+            &data.super_types
+        } else {
+            return None;
+        };
+        Some(declarations.get(sym).iter().map(|typ| {
+            let mut bindings = Bindings::new();
+            bindings.add_var_binding(var, make_variables_unique(typ));
+            bindings
+        }).collect())
+    }
+}
+
+impl Space for TypeIndex {
+    fn register_observer(&self, observer: Rc<RefCell<dyn SpaceObserver>>) {
+        self.space.register_observer(observer)
+    }
+    fn query(&self, query: &Atom) -> Vec<Bindings> {
+        match self.query_index(query) {
+            Some(result) => result,
+            None => self.space.query(query),
+        }
+    }
+    fn subst(&self, pattern: &Atom, template: &Atom) -> Vec<Atom> {
+        self.space.subst(pattern, template)
+    }
+}
+
+impl std::fmt::Debug for TypeIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let data = self.data.borrow();
+        write!(f, "TypeIndex{{ types: {}, super_types: {}, unindexed: {} }}",
+            data.types.0.len(), data.super_types.0.len(), data.unindexed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metta::metta_space;
+    use crate::metta::types::get_atom_types;
+
+    fn types(index: &TypeIndex, atom: &str) -> Vec<Atom> {
+        let typ = VariableAtom::new("X");
+        let query = Atom::expr([HAS_TYPE_SYMBOL, Atom::sym(atom), Atom::Variable(typ.clone())]);
+        let mut result: Vec<Atom> = index.query(&query).drain(0..)
+            .map(|mut bindings| bindings.resolve_and_remove(&typ).unwrap())
+            .collect();
+        result.sort_by_key(|t| t.to_string());
+        result
+    }
+
+    #[test]
+    fn type_index_tracks_modifications() {
+        let space = Shared::new(metta_space("(: a A) (: b B)"));
+        let index = TypeIndex::new(space.clone());
+
+        assert_eq!(types(&index, "a"), vec![expr!("A")]);
+        space.borrow_mut().add(expr!(":" "a" "C"));
+        assert_eq!(types(&index, "a"), vec![expr!("A"), expr!("C")]);
+        space.borrow_mut().remove(&expr!(":" "a" "A"));
+        assert_eq!(types(&index, "a"), vec![expr!("C")]);
+        space.borrow_mut().replace(&expr!(":" "b" "B"), expr!(":" "b" "D"));
+        assert_eq!(types(&index, "b"), vec![expr!("D")]);
+        assert_eq!(index.query(&expr!(":" "b" "D")), vec![Bindings::new()]);
+    }
+
+    #[test]
+    fn type_index_tracks_nested_spaces() {
+        let space = Shared::new(metta_space("(: a A)"));
+        let module = Shared::new(metta_space("(:< A B)"));
+        let index = TypeIndex::new(space.clone());
+
+        space.borrow_mut().add(Atom::gnd(module.clone()));
+        assert_eq!(get_atom_types(&index, &expr!("a")), vec![expr!("A"), expr!("B")]);
+        module.borrow_mut().add(expr!(":<" "B" "C"));
+        assert_eq!(get_atom_types(&index, &expr!("a")), vec![expr!("A"), expr!("B"), expr!("C")]);
+
+        space.borrow_mut().remove(&Atom::gnd(module.clone()));
+        module.borrow_mut().add(expr!(":<" "A" "D"));
+        assert_eq!(get_atom_types(&index, &expr!("a")), vec![expr!("A")]);
+    }
+
+    #[test]
+    fn type_index_falls_back_to_queries() {
+        let space = Shared::new(metta_space("(: a A)"));
+        let index = TypeIndex::new(space.clone());
+
+        space.borrow_mut().add(expr!(":" x "B"));
+        assert_eq!(types(&index, "a"), vec![expr!("A"), expr!("B")]);
+        space.borrow_mut().remove(&expr!(":" x "B"));
+        assert_eq!(types(&index, "a"), vec![expr!("A")]);
+
+        let module = Shared::new(metta_space("(: a C)"));
+        space.borrow_mut().add(Atom::gnd(module.clone()));
+        space.borrow_mut().add(Atom::gnd(module.clone()));
+        assert_eq!(index.data.borrow().unindexed, 1);
+        assert_eq!(types(&index, "a"), vec![expr!("A"), expr!("C"), expr!("C")]);
+        space.borrow_mut().remove(&Atom::gnd(module.clone()));
+        assert_eq!(index.data.borrow().unindexed, 0);
+        assert_eq!(types(&index, "a"), vec![expr!("A"), expr!("C")]);
+    }
+}
@@ -17,6 +17,10 @@
 //!
 //! When atom has no type assigned by user it has type `%Undefined%`. The value
 //! of `%Undefined%` type can be matched with any type required.
+//!
+//! Strict type checking (see [validate_atom_strict]) doesn't allow passing
+//! atoms without declared type where specific type is expected. Atom can be
+//! explicitly declared as `(: a %Undefined%)` to be accepted anyway.
 
 use super::*;
 use crate::atom::matcher::{Bindings, apply_bindings_to_atom};
@@ -57,13 +61,20 @@ fn add_super_types(space: &dyn Space, sub_types: &mut Vec<Atom>, from: usize) {
     }
 }
 
-fn check_types(actual: &[Vec<Atom>], expected: &[Atom], bindings: &mut Bindings) -> bool {
+fn get_super_types(space: &dyn Space, typ: &Atom) -> Vec<Atom> {
+    let mut types = vec![typ.clone()];
+    add_super_types(space, &mut types, 0);
+    types.remove(0);
+    types
+}
+
+fn check_types(space: &dyn Space, actual: &[Vec<Atom>], expected: &[Atom], bindings: &mut Bindings) -> bool {
     log::trace!("check_types: actual: {:?}, expected: {:?}, bindings: {}", actual, expected, bindings);
     match (actual, expected) {
         ([actual, actual_tail @ ..], [expected, expected_tail @ ..]) => {
             actual.iter().map(|actual| {
-                match_reducted_types(actual, expected, bindings)
-                    && check_types(actual_tail, expected_tail, bindings)
+                match_subtype(space, actual, expected, bindings)
+                    && check_types(space, actual_tail, expected_tail, bindings)
             }).any(std::convert::identity)
         },
         ([], []) => true,
@@ -156,7 +167,11 @@ pub fn get_atom_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
 }
 
 fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
-    log::trace!("get_reducted_types: atom: {}", atom);
+    reducted_types(space, atom, false)
+}
+
+fn reducted_types(space: &dyn Space, atom: &Atom, strict: bool) -> Vec<Atom> {
+    log::trace!("get_reducted_types: atom: {}, strict: {}", atom, strict);
     let types = match atom {
         Atom::Variable(_) => vec![ATOM_TYPE_UNDEFINED],
         Atom::Grounded(gnd) => {
@@ -166,7 +181,7 @@ fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
         Atom::Symbol(_) => {
             let mut types = query_types(space, atom);
             if types.is_empty() {
-                types.push(ATOM_TYPE_UNDEFINED)
+                types.push(undeclared_type(strict))
             }
             types
         },
@@ -177,7 +192,7 @@ fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
                 // TODO: it is not straightforward, if (: a (-> B C)) then
                 // what should we return for (d (a b)): (D ((-> B C) B)) or
                 // (D C) or both? Same question for a function call.
-                let child_types = get_reducted_types(space, child);
+                let child_types = reducted_types(space, child, strict);
                 let not_a_function_call = |typ: &&Atom| { i != 0 || !is_func(typ) };
                 let child_types = child_types.iter().filter(not_a_function_call);
                 tuples = child_types.flat_map(|typ| -> Vec<Vec<Atom>> {
@@ -189,8 +204,11 @@ fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
                 }).collect();
             }
             // if all members of tuple is Undefined then whole tuple is Undefined
+            let is_undefined = |child: &Atom| *child == ATOM_TYPE_UNDEFINED || is_undeclared(child);
+            let undeclared = tuples.iter()
+                .any(|children| children.iter().all(is_undefined) && children.iter().any(is_undeclared));
             let mut types: Vec<Atom> = tuples.drain(0..)
-                .filter(|children| children.iter().any(|child| *child != ATOM_TYPE_UNDEFINED))
+                .filter(|children| !children.iter().all(is_undefined))
                 .map(Atom::expr).collect();
             types.append(&mut query_types(space, atom));
             add_super_types(space, &mut types, 0);
@@ -201,23 +219,22 @@ fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
             if !expr.children().is_empty() {
                 let op = get_op(expr);
                 let args = get_args(expr);
-                let actual_arg_types: Vec<Vec<Atom>> = args.iter()
-                    .map(|arg| {
-                        let mut types = get_reducted_types(space, arg);
-                        types.push(ATOM_TYPE_ATOM);
-                        types.push(get_meta_type(arg));
-                        types
-                    }).collect();
-                let mut fn_types = get_reducted_types(space, op);
+                let actual_arg_types = get_actual_arg_types(space, args, strict);
+                let mut fn_types = reducted_types(space, op, strict);
                 let fn_types = fn_types.drain(0..).filter(is_func);
+                let from = types.len();
                 for fn_type in fn_types {
                     only_tuple = false;
                     let (expected_arg_types, ret_typ) = get_arg_types(&fn_type);
                     let mut bindings = Bindings::new();
-                    if check_types(actual_arg_types.as_slice(), expected_arg_types, &mut bindings) {
-                        types.push(apply_bindings_to_atom(&ret_typ, &bindings));
+                    if check_types(space, actual_arg_types.as_slice(), expected_arg_types, &mut bindings) {
+                        let ret_typ = apply_bindings_to_atom(&ret_typ, &bindings);
+                        if !types.contains(&ret_typ) {
+                            types.push(ret_typ);
+                        }
                     }
                 }
+                add_super_types(space, &mut types, from);
                 log::trace!("get_reducted_types: tuple + function {} types {:?}", atom, types);
             }
 
@@ -232,7 +249,7 @@ fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
             // separate tuples and calls in separate Atom types. Or use
             // embedded atom to designate function call.
             if only_tuple && types.is_empty() {
-                types.push(ATOM_TYPE_UNDEFINED)
+                types.push(if undeclared { undeclared_type(strict) } else { ATOM_TYPE_UNDEFINED })
             }
             types
         },
@@ -241,6 +258,15 @@ fn get_reducted_types(space: &dyn Space, atom: &Atom) -> Vec<Atom> {
     types
 }
 
+fn get_actual_arg_types(space: &dyn Space, args: &[Atom], strict: bool) -> Vec<Vec<Atom>> {
+    args.iter().map(|arg| {
+        let mut types = reducted_types(space, arg, strict);
+        types.push(ATOM_TYPE_ATOM);
+        types.push(get_meta_type(arg));
+        types
+    }).collect()
+}
+
 #[derive(Clone, PartialEq, Debug)]
 struct UndefinedTypeMatch { }
 
@@ -264,6 +290,59 @@ impl Grounded for UndefinedTypeMatch {
     }
 }
 
+/// Type of the atom which has no type declared when strict type checking is
+/// used. Unlike `%Undefined%` it matches only `%Undefined%` and type
+/// variables, thus such atom cannot be passed where specific type is
+/// expected.
+#[derive(Clone, PartialEq, Debug)]
+struct UndeclaredType { }
+
+impl std::fmt::Display for UndeclaredType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", ATOM_TYPE_UNDEFINED)
+    }
+}
+
+impl Grounded for UndeclaredType {
+    fn type_(&self) -> Atom {
+        ATOM_TYPE_TYPE
+    }
+
+    fn match_(&self, other: &Atom) -> crate::matcher::MatchResultIter {
+        let mut bindings = Bindings::new();
+        let matched = match other {
+            Atom::Variable(var) => bindings.add_var_binding(var, Atom::gnd(self.clone())),
+            _ => *other == ATOM_TYPE_UNDEFINED || is_undeclared(other)
+                || *other == Atom::gnd(UndefinedTypeMatch{}),
+        };
+        Box::new(Some(bindings).filter(|_| matched).into_iter())
+    }
+
+    fn execute(&self, _args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        execute_not_executable(self)
+    }
+}
+
+fn undeclared_type(strict: bool) -> Atom {
+    if strict {
+        Atom::gnd(UndeclaredType{})
+    } else {
+        ATOM_TYPE_UNDEFINED
+    }
+}
+
+fn is_undeclared(typ: &Atom) -> bool {
+    *typ == Atom::gnd(UndeclaredType{})
+}
+
+/// Replaces types of the undeclared atoms by `%Undefined%` to report them.
+fn restore_undefined_types(typ: &Atom) -> Atom {
+    let mut typ = typ.clone();
+    typ.iter_mut().filter(|atom| is_undeclared(atom))
+        .for_each(|atom| *atom = ATOM_TYPE_UNDEFINED);
+    typ
+}
+
 /// Matches two types and puts new variable bindings into `bindings`. Returns
 /// true when match is found. Function matches types using previous bindings
 /// passed. If match is not found some new bindings can still be added. If
@@ -306,6 +385,65 @@ pub fn match_reducted_types(left: &Atom, right: &Atom, bindings: &mut Bindings)
     matched
 }
 
+/// Matches `sub` type with `sup` type like [match_reducted_types] does, and
+/// also succeeds when `sub` is a subtype of `sup`. Subtype relation is
+/// defined by `:<` declarations, which can be parameterized like
+/// `(:< (List $t) (Seq $t))`, and is transitive. Function type `(-> A R)` is
+/// a subtype of `(-> B S)` when `B` is a subtype of `A` and `R` is a subtype
+/// of `S`. Bindings are changed only when match is found.
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::expr;
+/// use hyperon::matcher::Bindings;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::types::match_subtype;
+///
+/// let space = metta_space("(:< Cat Animal) (:< (List $t) (Seq $t))");
+/// let mut bindings = Bindings::new();
+///
+/// assert!(match_subtype(&space, &expr!("Cat"), &expr!("Animal"), &mut bindings));
+/// assert!(!match_subtype(&space, &expr!("Animal"), &expr!("Cat"), &mut bindings));
+/// assert!(match_subtype(&space, &expr!("List" "Cat"), &expr!("Seq" "Cat"), &mut bindings));
+/// assert!(match_subtype(&space, &expr!("->" "Animal" "Cat"), &expr!("->" "Cat" "Animal"), &mut bindings));
+/// assert!(!match_subtype(&space, &expr!("->" "Cat" "Cat"), &expr!("->" "Animal" "Cat"), &mut bindings));
+/// ```
+pub fn match_subtype(space: &dyn Space, sub: &Atom, sup: &Atom, bindings: &mut Bindings) -> bool {
+    let mut next = bindings.clone();
+    if match_reducted_types(sub, sup, &mut next) {
+        *bindings = next;
+        return true;
+    }
+    let sub = apply_bindings_to_atom(sub, bindings);
+    let sup = apply_bindings_to_atom(sup, bindings);
+    if is_func(&sub) && is_func(&sup) {
+        let (sub_args, sub_ret) = get_arg_types(&sub);
+        let (sup_args, sup_ret) = get_arg_types(&sup);
+        if sub_args.len() == sup_args.len() {
+            let mut next = bindings.clone();
+            let matched = sup_args.iter().zip(sub_args)
+                .all(|(sup_arg, sub_arg)| match_subtype(space, sup_arg, sub_arg, &mut next))
+                && match_subtype(space, sub_ret, sup_ret, &mut next);
+            if matched {
+                *bindings = next;
+                return true;
+            }
+        }
+    }
+    if let Atom::Variable(_) = sub {
+        return false;
+    }
+    for super_type in get_super_types(space, &sub) {
+        let mut next = bindings.clone();
+        if match_reducted_types(&super_type, &sup, &mut next) {
+            *bindings = next;
+            return true;
+        }
+    }
+    false
+}
+
 fn replace_undefined_types(atom: &Atom) -> Atom {
     let mut atom = atom.clone();
     atom.iter_mut().filter(|atom| **atom == ATOM_TYPE_UNDEFINED)
@@ -313,13 +451,13 @@ fn replace_undefined_types(atom: &Atom) -> Atom {
     atom
 }
 
-fn get_matched_types(space: &dyn Space, atom: &Atom, typ: &Atom) -> Vec<(Atom, Bindings)> {
-    let mut types = get_reducted_types(space, atom);
+fn get_matched_types(space: &dyn Space, atom: &Atom, typ: &Atom, strict: bool) -> Vec<(Atom, Bindings)> {
+    let mut types = reducted_types(space, atom, strict);
     types.drain(0..).filter_map(|t| {
         let mut bindings = Bindings::new();
         // TODO: write a unit test
         let t = make_variables_unique(&t);
-        if match_reducted_types(&t, typ, &mut bindings) {
+        if match_subtype(space, &t, typ, &mut bindings) {
             Some((t, bindings))
         } else {
             None
@@ -343,7 +481,11 @@ fn get_matched_types(space: &dyn Space, atom: &Atom, typ: &Atom) -> Vec<(Atom, B
 /// assert!(check_type(&space, &expr!("a"), &expr!("B")));
 /// ```
 pub fn check_type(space: &dyn Space, atom: &Atom, typ: &Atom) -> bool {
-    check_meta_type(atom, typ) || !get_matched_types(space, atom, typ).is_empty()
+    check_type_mode(space, atom, typ, false)
+}
+
+fn check_type_mode(space: &dyn Space, atom: &Atom, typ: &Atom, strict: bool) -> bool {
+    check_meta_type(atom, typ) || !get_matched_types(space, atom, typ, strict).is_empty()
 }
 
 /// Finds all types of the passed `atom` which matches the given `typ` in
@@ -367,7 +509,7 @@ pub fn get_type_bindings(space: &dyn Space, atom: &Atom, typ: &Atom) -> Vec<(Ato
     if check_meta_type(atom, typ) {
         result.push((typ.clone(), Bindings::new()));
     }
-    result.append(&mut get_matched_types(space, atom, typ));
+    result.append(&mut get_matched_types(space, atom, typ, false));
     if result.len() > 1 {
         result = result.drain(0..).filter(|(typ, _)| *typ != ATOM_TYPE_UNDEFINED).collect();
     }
@@ -403,7 +545,296 @@ fn check_meta_type(atom: &Atom, typ: &Atom) -> bool {
 /// assert!(!validate_atom(&space, &expr!("foo" "b")));
 /// ```
 pub fn validate_atom(space: &dyn Space, atom: &Atom) -> bool {
-    !get_reducted_types(space, atom).is_empty()
+    validate_atom_mode(space, atom, false)
+}
+
+fn validate_atom_mode(space: &dyn Space, atom: &Atom, strict: bool) -> bool {
+    !reducted_types(space, atom, strict).is_empty()
+}
+
+pub const BAD_ARG_TYPE_SYMBOL : Atom = sym!("BadArgType");
+pub const BAD_FUNCTION_CALL_SYMBOL : Atom = sym!("BadFunctionCall");
+pub const CANDIDATE_SYMBOL : Atom = sym!("Candidate");
+pub const EXPECTED_SYMBOL : Atom = sym!("Expected");
+pub const ACTUAL_SYMBOL : Atom = sym!("Actual");
+pub const UNRESOLVED_SYMBOL : Atom = sym!("Unresolved");
+
+/// Reason why the function type doesn't match the arguments of the call.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CallMismatch {
+    /// Argument at the `index` position of the expression has no type which
+    /// matches the `expected` one. `expected` type has type variables
+    /// resolved using previous arguments, `unresolved` contains variables
+    /// which are still free. `actual` contains types of the argument.
+    BadArgType{ index: usize, expected: Atom, actual: Vec<Atom>, unresolved: Vec<VariableAtom> },
+    /// Number of arguments is different from the number of arguments in the
+    /// function type.
+    IncorrectNumberOfArguments{ expected: usize, actual: usize },
+}
+
+impl CallMismatch {
+    /// Represents the mismatch as a MeTTa expression, for instance
+    /// `(BadArgType 1 (Expected A) (Actual B))`.
+    pub fn as_atom(&self) -> Atom {
+        match self {
+            CallMismatch::BadArgType{ index, expected, actual, unresolved } => {
+                let mut actual_atom = vec![ACTUAL_SYMBOL];
+                actual_atom.extend(actual.iter().cloned());
+                let mut children = vec![BAD_ARG_TYPE_SYMBOL, Atom::sym(index.to_string()),
+                    Atom::expr([EXPECTED_SYMBOL, expected.clone()]), Atom::expr(actual_atom)];
+                if !unresolved.is_empty() {
+                    let mut unresolved_atom = vec![UNRESOLVED_SYMBOL];
+                    unresolved_atom.extend(unresolved.iter().cloned().map(Atom::Variable));
+                    children.push(Atom::expr(unresolved_atom));
+                }
+                Atom::expr(children)
+            },
+            CallMismatch::IncorrectNumberOfArguments{ expected, actual } =>
+                Atom::expr([INCORRECT_NUMBER_OF_ARGUMENTS_SYMBOL,
+                    Atom::expr([EXPECTED_SYMBOL, Atom::sym(expected.to_string())]),
+                    Atom::expr([ACTUAL_SYMBOL, Atom::sym(actual.to_string())])]),
+        }
+    }
+}
+
+/// Detailed description of the type error.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TypeError {
+    /// `atom` has no type which matches the `expected` type, `actual`
+    /// contains types of the atom.
+    BadType{ atom: Atom, expected: Atom, actual: Vec<Atom> },
+    /// `atom` is a function call which doesn't match any type of the
+    /// function. `candidates` contains function types tried and the reason
+    /// each of them doesn't match.
+    BadFunctionCall{ atom: Atom, candidates: Vec<(Atom, CallMismatch)> },
+}
+
+impl TypeError {
+    /// Returns sub-expression which is typed incorrectly.
+    pub fn atom(&self) -> &Atom {
+        match self {
+            TypeError::BadType{ atom, .. } => atom,
+            TypeError::BadFunctionCall{ atom, .. } => atom,
+        }
+    }
+
+    /// Represents the error as a MeTTa expression, for instance
+    /// `(BadFunctionCall (foo b) (Candidate (-> A B) (BadArgType 1
+    /// (Expected A) (Actual B))))`.
+    pub fn as_atom(&self) -> Atom {
+        match self {
+            TypeError::BadType{ atom, expected, actual } => {
+                let mut actual_atom = vec![ACTUAL_SYMBOL];
+                actual_atom.extend(actual.iter().cloned());
+                Atom::expr([BAD_TYPE_SYMBOL, atom.clone(),
+                    Atom::expr([EXPECTED_SYMBOL, expected.clone()]), Atom::expr(actual_atom)])
+            },
+            TypeError::BadFunctionCall{ atom, candidates } => {
+                let mut children = vec![BAD_FUNCTION_CALL_SYMBOL, atom.clone()];
+                children.extend(candidates.iter().map(|(fn_type, mismatch)|
+                    Atom::expr([CANDIDATE_SYMBOL, fn_type.clone(), mismatch.as_atom()])));
+                Atom::expr(children)
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_atom())
+    }
+}
+
+fn get_call_mismatch(space: &dyn Space, args: &[Atom], actual_arg_types: &[Vec<Atom>], fn_type: &Atom, strict: bool) -> CallMismatch {
+    let (expected_arg_types, _ret_typ) = get_arg_types(fn_type);
+    if expected_arg_types.len() != args.len() {
+        return CallMismatch::IncorrectNumberOfArguments{
+            expected: expected_arg_types.len(), actual: args.len() };
+    }
+    // check_types() tries all combinations of the argument types, thus if
+    // the call doesn't match then the greedy search below finds the first
+    // argument which cannot be matched
+    let mut bindings = Bindings::new();
+    for (i, (actual, expected)) in actual_arg_types.iter().zip(expected_arg_types).enumerate() {
+        let matched = actual.iter().find_map(|actual| {
+            let mut next = bindings.clone();
+            if match_subtype(space, actual, expected, &mut next) {
+                Some(next)
+            } else {
+                None
+            }
+        });
+        match matched {
+            Some(next) => bindings = next,
+            None => {
+                let expected = apply_bindings_to_atom(expected, &bindings);
+                let mut unresolved = Vec::new();
+                expected.iter().filter_map(AtomIter::extract_var).for_each(|var| {
+                    if !unresolved.contains(var) {
+                        unresolved.push(var.clone());
+                    }
+                });
+                let actual = reducted_types(space, &args[i], strict).iter()
+                    .map(restore_undefined_types).collect();
+                return CallMismatch::BadArgType{ index: i + 1, expected, actual, unresolved };
+            },
+        }
+    }
+    panic!("Function type {} is expected to not match arguments {:?}", fn_type, args)
+}
+
+fn get_type_error(space: &dyn Space, atom: &Atom, strict: bool) -> TypeError {
+    let expr = match atom {
+        Atom::Expression(expr) if !expr.children().is_empty() => expr,
+        _ => panic!("Only non-empty expression can be typed incorrectly, found: {}", atom),
+    };
+    let args = get_args(expr);
+    let actual_arg_types = get_actual_arg_types(space, args, strict);
+    let mut fn_types = reducted_types(space, get_op(expr), strict);
+    let candidates: Vec<(Atom, CallMismatch)> = fn_types.drain(0..)
+        .filter(is_func)
+        .map(|fn_type| {
+            let mismatch = get_call_mismatch(space, args, &actual_arg_types, &fn_type, strict);
+            (fn_type, mismatch)
+        }).collect();
+    // badly typed sub-expression is a more precise reason of the error
+    let bad_arg = candidates.iter()
+        .filter_map(|(_, mismatch)| match mismatch {
+            CallMismatch::BadArgType{ index, .. } => Some(&expr.children()[*index]),
+            _ => None,
+        })
+        .find(|arg| !validate_atom_mode(space, arg, strict));
+    let bad_child = bad_arg.or_else(|| if candidates.is_empty() {
+        expr.children().iter().find(|child| !validate_atom_mode(space, child, strict))
+    } else {
+        None
+    });
+    match bad_child {
+        Some(child) => get_type_error(space, child, strict),
+        None => TypeError::BadFunctionCall{ atom: atom.clone(), candidates },
+    }
+}
+
+/// Checks if atom is typed correctly like [validate_atom] does, and returns
+/// [TypeError] which describes the incorrectly typed sub-expression when
+/// it is not.
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::expr;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::types::{validate_atom_detailed, TypeError, CallMismatch};
+///
+/// let space = metta_space("(: foo (-> A B)) (: a A) (: b B)");
+///
+/// assert_eq!(validate_atom_detailed(&space, &expr!("foo" "a")), Ok(()));
+/// assert_eq!(validate_atom_detailed(&space, &expr!("foo" "b")),
+///     Err(TypeError::BadFunctionCall{ atom: expr!("foo" "b"), candidates: vec![
+///         (expr!("->" "A" "B"), CallMismatch::BadArgType{ index: 1,
+///             expected: expr!("A"), actual: vec![expr!("B")], unresolved: vec![] })] }));
+/// ```
+pub fn validate_atom_detailed(space: &dyn Space, atom: &Atom) -> Result<(), TypeError> {
+    validate_atom_detailed_mode(space, atom, false)
+}
+
+/// Checks if atom is typed correctly like [validate_atom_detailed] does, but
+/// atoms which have no type declared cannot be used where specific type is
+/// expected. Atoms explicitly declared as `(: a %Undefined%)` and arguments
+/// of the functions which have `%Undefined%`, `Atom` or type variable as an
+/// argument type are still accepted.
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::expr;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::types::{validate_atom_detailed, validate_atom_strict};
+///
+/// let space = metta_space("(: foo (-> A B)) (: a A) (: c %Undefined%)");
+///
+/// assert!(validate_atom_detailed(&space, &expr!("foo" "typo")).is_ok());
+/// assert!(validate_atom_strict(&space, &expr!("foo" "typo")).is_err());
+/// assert!(validate_atom_strict(&space, &expr!("foo" "a")).is_ok());
+/// assert!(validate_atom_strict(&space, &expr!("foo" "c")).is_ok());
+/// ```
+pub fn validate_atom_strict(space: &dyn Space, atom: &Atom) -> Result<(), TypeError> {
+    validate_atom_detailed_mode(space, atom, true)
+}
+
+fn validate_atom_detailed_mode(space: &dyn Space, atom: &Atom, strict: bool) -> Result<(), TypeError> {
+    if validate_atom_mode(space, atom, strict) {
+        Ok(())
+    } else {
+        Err(get_type_error(space, atom, strict))
+    }
+}
+
+/// Checks if `atom` has the given `typ` like [check_type] does, and returns
+/// [TypeError] which describes the reason when it is not.
+pub fn check_type_detailed(space: &dyn Space, atom: &Atom, typ: &Atom) -> Result<(), TypeError> {
+    check_type_detailed_mode(space, atom, typ, false)
+}
+
+fn check_type_detailed_mode(space: &dyn Space, atom: &Atom, typ: &Atom, strict: bool) -> Result<(), TypeError> {
+    if check_type_mode(space, atom, typ, strict) {
+        Ok(())
+    } else {
+        validate_atom_detailed_mode(space, atom, strict)?;
+        Err(TypeError::BadType{ atom: atom.clone(), expected: typ.clone(),
+            actual: reducted_types(space, atom, strict).iter().map(restore_undefined_types).collect() })
+    }
+}
+
+/// Checks types of the `=` rule without evaluating it. Both sides of the
+/// rule should be typed correctly and the right side should have one of the
+/// types of the left side. When none of the types of the left side match
+/// the error for the first one is returned. Panics when `rule` is not an
+/// `(= <left> <right>)` expression.
+///
+/// # Examples
+///
+/// ```
+/// use hyperon::expr;
+/// use hyperon::metta::metta_space;
+/// use hyperon::metta::types::check_rule;
+///
+/// let space = metta_space("(: foo (-> A B)) (: a A) (: b B)");
+///
+/// assert!(check_rule(&space, &expr!("=" ("foo" "a") "b")).is_ok());
+/// assert!(check_rule(&space, &expr!("=" ("foo" "a") "a")).is_err());
+/// assert!(check_rule(&space, &expr!("=" ("foo" "b") "b")).is_err());
+/// ```
+pub fn check_rule(space: &dyn Space, rule: &Atom) -> Result<(), TypeError> {
+    check_rule_mode(space, rule, false)
+}
+
+/// Checks types of the `=` rule like [check_rule] does, using strict type
+/// checking of [validate_atom_strict]. The right side of the rule which
+/// defines a function without declared type is not required to have any
+/// specific type.
+pub fn check_rule_strict(space: &dyn Space, rule: &Atom) -> Result<(), TypeError> {
+    check_rule_mode(space, rule, true)
+}
+
+fn check_rule_mode(space: &dyn Space, rule: &Atom, strict: bool) -> Result<(), TypeError> {
+    let (left, right) = match rule {
+        Atom::Expression(expr) => match expr.children().as_slice() {
+            [eq, left, right] if *eq == EQUAL_SYMBOL => (left, right),
+            _ => panic!("Incorrect rule: {}", rule),
+        },
+        _ => panic!("Incorrect rule: {}", rule),
+    };
+    validate_atom_detailed_mode(space, left, strict)?;
+    validate_atom_detailed_mode(space, right, strict)?;
+    let mut first_error = None;
+    for typ in get_atom_types(space, left) {
+        match check_type_detailed_mode(space, right, &typ, strict) {
+            Ok(()) => return Ok(()),
+            Err(err) => { first_error.get_or_insert(err); },
+        }
+    }
+    first_error.map_or(Ok(()), Err)
 }
 
 #[cfg(test)]
@@ -411,6 +842,7 @@ mod tests {
     use super::*;
     use crate::metta::metta_space;
     use crate::metta::metta_atom as atom;
+    use crate::atom::matcher::atoms_are_equivalent;
     
     fn grammar_space() -> GroundingSpace {
         let mut space = GroundingSpace::new();
@@ -937,4 +1369,178 @@ mod tests {
         assert!(validate_atom(&space, &atom("(varF (varR a))")));
         assert!(!validate_atom(&space, &atom("(varF (atomR a))")));
     }
+
+    #[test]
+    fn validate_atom_detailed_reports_nested_argument() {
+        let space = metta_space("
+            (: foo (-> A B))
+            (: bar (-> B C))
+            (: a A)
+            (: b B)
+        ");
+
+        assert_eq!(validate_atom_detailed(&space, &atom("(bar (foo b))")),
+            Err(TypeError::BadFunctionCall{ atom: atom("(foo b)"), candidates: vec![
+                (atom("(-> A B)"), CallMismatch::BadArgType{ index: 1,
+                    expected: atom("A"), actual: vec![atom("B")], unresolved: vec![] })] }));
+    }
+
+    #[test]
+    fn validate_atom_detailed_reports_all_candidates() {
+        let space = metta_space("
+            (: foo (-> A B))
+            (: foo (-> A A B))
+            (: b B)
+        ");
+
+        match validate_atom_detailed(&space, &atom("(foo b)")) {
+            Err(TypeError::BadFunctionCall{ atom: bad, candidates }) => {
+                assert_eq!(bad, atom("(foo b)"));
+                let candidates: Vec<Atom> = candidates.iter()
+                    .map(|(fn_type, mismatch)| Atom::expr([fn_type.clone(), mismatch.as_atom()]))
+                    .collect();
+                assert_eq_no_order!(candidates, vec![
+                    expr!(("->" "A" "B") ("BadArgType" "1" ("Expected" "A") ("Actual" "B"))),
+                    expr!(("->" "A" "A" "B") ("IncorrectNumberOfArguments" ("Expected" "2") ("Actual" "1"))),
+                ]);
+            },
+            result => panic!("BadFunctionCall error is expected, found: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn validate_atom_detailed_reports_unresolved_variables() {
+        let space = metta_space("
+            (: eq (-> $t $t Bool))
+            (: len (-> (List $t) Number))
+            (: a A)
+            (: b B)
+        ");
+
+        let error = validate_atom_detailed(&space, &atom("(eq a b)")).unwrap_err();
+        assert!(atoms_are_equivalent(&error.as_atom(), &expr!("BadFunctionCall" ("eq" "a" "b")
+            ("Candidate" ("->" t t "Bool") ("BadArgType" "2" ("Expected" "A") ("Actual" "B"))))));
+        let error = validate_atom_detailed(&space, &atom("(len a)")).unwrap_err();
+        assert!(atoms_are_equivalent(&error.as_atom(), &expr!("BadFunctionCall" ("len" "a")
+            ("Candidate" ("->" ("List" t) "Number") ("BadArgType" "1" ("Expected" ("List" t)) ("Actual" "A") ("Unresolved" t))))));
+    }
+
+    #[test]
+    fn check_type_detailed_reports_actual_types() {
+        let space = metta_space("
+            (: a A)
+            (: a B)
+        ");
+
+        assert_eq!(check_type_detailed(&space, &atom("a"), &atom("A")), Ok(()));
+        match check_type_detailed(&space, &atom("a"), &atom("C")) {
+            Err(TypeError::BadType{ atom: bad, expected, actual }) => {
+                assert_eq!(bad, atom("a"));
+                assert_eq!(expected, atom("C"));
+                assert_eq_no_order!(actual, vec![atom("A"), atom("B")]);
+            },
+            result => panic!("BadType error is expected, found: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn check_rule_checks_both_sides() {
+        let space = metta_space("
+            (: foo (-> A B))
+            (: bar (-> B C))
+            (: a A)
+            (: b B)
+        ");
+
+        assert_eq!(check_rule(&space, &atom("(= (foo $x) (foo a))")), Ok(()));
+        assert_eq!(check_rule(&space, &atom("(= (bar $x) (foo a))")).map_err(|e| e.atom().clone()),
+            Err(atom("(foo a)")));
+        assert_eq!(check_rule(&space, &atom("(= (bar b) (bar a))")).map_err(|e| e.atom().clone()),
+            Err(atom("(bar a)")));
+        assert_eq!(check_rule(&space, &atom("(= (bar a) (bar b))")).map_err(|e| e.atom().clone()),
+            Err(atom("(bar a)")));
+        assert_eq!(check_rule(&space, &atom("(= (bar $x) $y)")), Ok(()));
+        assert_eq!(check_rule(&space, &atom("(= (baz $x) (bar b))")), Ok(()));
+    }
+
+    #[test]
+    fn validate_atom_strict_rejects_undeclared_atoms() {
+        let space = metta_space("
+            (: foo (-> A B))
+            (: id (-> $t $t))
+            (: = (-> $t $t Type))
+            (: a A)
+            (: c %Undefined%)
+        ");
+
+        assert_eq!(validate_atom_strict(&space, &atom("(foo typo)")),
+            Err(TypeError::BadFunctionCall{ atom: atom("(foo typo)"), candidates: vec![
+                (atom("(-> A B)"), CallMismatch::BadArgType{ index: 1, expected: atom("A"),
+                    actual: vec![ATOM_TYPE_UNDEFINED], unresolved: vec![] })] }));
+        assert!(validate_atom_strict(&space, &atom("(foo (typo a))")).is_err());
+        assert_eq!(validate_atom_strict(&space, &atom("(foo a)")), Ok(()));
+        assert_eq!(validate_atom_strict(&space, &atom("(foo c)")), Ok(()));
+        assert_eq!(validate_atom_strict(&space, &atom("(id typo)")), Ok(()));
+        assert_eq!(validate_atom_strict(&space, &atom("(typo a)")), Ok(()));
+        assert_eq!(validate_atom_strict(&space, &atom("(= (bar) typo)")), Ok(()));
+        assert_eq!(validate_atom_detailed(&space, &atom("(foo typo)")), Ok(()));
+    }
+
+    #[test]
+    fn check_rule_strict_rejects_undeclared_atoms() {
+        let space = metta_space("
+            (: foo (-> A B))
+            (: b B)
+        ");
+
+        assert_eq!(check_rule_strict(&space, &atom("(= (foo $x) b)")), Ok(()));
+        assert_eq!(check_rule_strict(&space, &atom("(= (foo $x) typo)")),
+            Err(TypeError::BadType{ atom: atom("typo"), expected: atom("B"),
+                actual: vec![ATOM_TYPE_UNDEFINED] }));
+        assert_eq!(check_rule(&space, &atom("(= (foo $x) typo)")), Ok(()));
+        assert_eq!(check_rule_strict(&space, &atom("(= (bar $x) typo)")), Ok(()));
+    }
+
+    #[test]
+    fn match_subtype_parameterized_and_function_types() {
+        let space = metta_space("
+            (:< Cat Animal)
+            (:< Animal Entity)
+            (:< (List $t) (Seq $t))
+        ");
+        let check = |sub: &str, sup: &str| match_subtype(&space, &atom(sub), &atom(sup), &mut Bindings::new());
+
+        assert!(check("Cat", "Entity"));
+        assert!(check("(List Cat)", "(Seq Cat)"));
+        assert!(check("(List Cat)", "(Seq $t)"));
+        assert!(!check("(List Cat)", "(Seq Animal)"));
+        assert!(check("(-> Animal Cat)", "(-> Cat Animal)"));
+        assert!(check("(-> Entity (List Cat))", "(-> Cat (Seq Cat))"));
+        assert!(!check("(-> Cat Cat)", "(-> Animal Cat)"));
+        assert!(!check("(-> Animal Animal)", "(-> Animal Cat)"));
+        assert!(!check("(-> Animal Cat)", "(-> Cat Cat Cat)"));
+
+        let mut bindings = Bindings::new();
+        assert!(match_subtype(&space, &atom("(List Cat)"), &atom("(Seq $t)"), &mut bindings));
+        assert_eq!(bindings.resolve(&VariableAtom::new("t")), Some(atom("Cat")));
+    }
+
+    #[test]
+    fn validate_atom_checks_function_subtypes() {
+        let space = metta_space("
+            (:< Cat Animal)
+            (: apply-to-cat (-> (-> Cat Animal) Cat Animal))
+            (: tom Cat)
+            (: breed (-> Animal Cat))
+            (: meow (-> Cat Cat))
+            (: bark (-> Dog Cat))
+            (: get-cat (-> Cat))
+            (: name (-> Animal Symbol))
+        ");
+
+        assert!(validate_atom(&space, &atom("(apply-to-cat breed tom)")));
+        assert!(validate_atom(&space, &atom("(apply-to-cat meow tom)")));
+        assert!(!validate_atom(&space, &atom("(apply-to-cat bark tom)")));
+        assert!(validate_atom(&space, &atom("(name (get-cat))")));
+    }
 }
@@ -72,3 +72,44 @@ fn test_case_operation() {
     "));
     assert_eq_metta_results!(result, expected);
 }
+
+#[test]
+fn test_case_guards_and_default() {
+    let metta = new_metta_rust();
+    let result = metta.run(&mut SExprParser::new("
+        (= (positive 1) False)
+        (= (positive 2) True)
+
+        ; guard is evaluated after pattern is matched
+        !(case (superpose ((num 1) (num 2)))
+          (((num $x) (positive $x) (positive $x))
+           ((num $x) (not-positive $x))))
+
+        ; else is used when result is empty or no pattern matched
+        !(case (superpose ())
+          ((A a)
+           (else no-result)))
+        !(case (superpose (A B))
+          ((A a)
+           (else other)))
+    "));
+    let expected = metta.run(&mut SExprParser::new("
+        ! (superpose ((not-positive 1) (positive 2)))
+        ! no-result
+        ! (superpose (a other))
+    "));
+    assert_eq_metta_results!(result, expected);
+}
+
+#[test]
+fn test_case_propagates_errors() {
+    let metta = new_metta_rust();
+
+    // else doesn't catch an error of the atom
+    let result = metta.run(&mut SExprParser::new("!(case (/ 1 0) ((else fallback)))"));
+    assert_eq!(format!("{:?}", result), "Ok([[(Error (/ 1 0) Division by zero)]])");
+
+    // else doesn't catch an error of the guard
+    let result = metta.run(&mut SExprParser::new("!(case 1 (($x (/ 1 0) guarded) (else fallback)))"));
+    assert_eq!(format!("{:?}", result), "Ok([[(Error (/ 1 0) Division by zero)]])");
+}
@@ -0,0 +1,74 @@
+use hyperon::*;
+use hyperon::matcher::{Bindings, MatchResultIter, match_atoms};
+use hyperon::metta::metta_space;
+use hyperon::metta::interpreter::interpret;
+
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(type = "Color", display = "red")]
+struct Red;
+
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(type = "(-> Color Color)", display = "invert", execute = "invert")]
+struct Invert;
+
+impl Invert {
+    fn invert(&self, args: &mut Vec<Atom>) -> Result<Vec<Atom>, ExecError> {
+        match args.as_slice() {
+            [color] if *color == Atom::gnd(Red) => Ok(vec![sym!("cyan")]),
+            _ => Err("invert expects red color".into()),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(type = sym!("Name"), match_ = "match_name")]
+struct Name(&'static str);
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "\"{}\"", self.0)
+    }
+}
+
+impl Name {
+    fn match_name(&self, other: &Atom) -> MatchResultIter {
+        match other {
+            Atom::Symbol(sym) if sym.name() == self.0 => Box::new(std::iter::once(Bindings::new())),
+            _ => match_by_equality(self, other),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Grounded)]
+#[grounded(display = "wrapper", match_by_equality)]
+struct Wrapper<T: Clone + PartialEq + std::fmt::Debug + 'static>(T);
+
+#[test]
+fn derive_grounded_type_and_display() {
+    assert_eq!(Red.type_(), sym!("Color"));
+    assert_eq!(Invert.type_(), expr!("->" "Color" "Color"));
+    assert_eq!(Name("a").type_(), sym!("Name"));
+    assert_eq!(Wrapper(1u8).type_(), rust_type_atom::<Wrapper<u8>>());
+    assert_eq!(Atom::gnd(Red).to_string(), "red");
+    assert_eq!(Atom::gnd(Name("a")).to_string(), "\"a\"");
+    assert_eq!(Atom::gnd(Wrapper(1u8)).to_string(), "wrapper");
+}
+
+#[test]
+fn derive_grounded_execute() {
+    let space = metta_space("");
+    let expr = Atom::expr([Atom::gnd(Invert), Atom::gnd(Red)]);
+
+    assert_eq!(Red.execute(&mut vec![]), Err(ExecError::NoReduce));
+    assert_eq!(interpret(&space, &expr), Ok(vec![sym!("cyan")]));
+    assert_eq!(Invert.execute(&mut vec![sym!("blue")]), Err(ExecError::from("invert expects red color")));
+}
+
+#[test]
+fn derive_grounded_match() {
+    assert_eq!(Atom::gnd(Wrapper(1u8)), Atom::gnd(Wrapper(1u8)));
+    assert_ne!(Atom::gnd(Wrapper(1u8)), Atom::gnd(Wrapper(2u8)));
+    assert_eq!(match_atoms(&Atom::gnd(Name("a")), &sym!("a")).count(), 1);
+    assert_eq!(match_atoms(&Atom::gnd(Name("a")), &Atom::gnd(Name("a"))).count(), 1);
+    assert_eq!(match_atoms(&Atom::gnd(Name("a")), &sym!("b")).count(), 0);
+}
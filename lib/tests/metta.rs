@@ -1,5 +1,6 @@
 use hyperon::metta::text::*;
 use hyperon::metta::runner::new_metta_rust;
+use hyperon::metta::runner::capabilities::Capability;
 
 #[test]
 fn test_reduce_higher_order() {
@@ -19,3 +20,221 @@ fn test_reduce_higher_order() {
 
     assert_eq!(result, Ok(vec![vec![]]));
 }
+
+#[test]
+fn test_comparison_and_bool_ops() {
+    let program = "
+        (= (fact $n) (if (<= $n 1) 1 (* $n (fact (- $n 1)))))
+
+        !(assertEqualToResult (fact 4) (24))
+        !(assertEqualToResult (and (> 3 2.5) (not (>= 1 2))) (True))
+        !(assertEqualToResult (or (< 2 1) (== 2 2.0)) (True))
+        !(assertEqualToResult (== (a b) (a c)) (False))
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    assert_eq!(result, Ok(vec![vec![], vec![], vec![], vec![]]));
+}
+
+#[test]
+fn test_string_ops() {
+    let program = r#"
+        !(assertEqualToResult (str-concat "foo" "\tbar") ("foo\tbar"))
+        !(assertEqualToResult (str-length "h\u{e9}llo") (5))
+        !(assertEqualToResult (str-split "a b" " ") (("a" "b")))
+        !(assertEqualToResult (str-join "-" ("a" "b")) ("a-b"))
+        !(assertEqualToResult (str-format "{} is {}" ("x" 42)) ("x is 42"))
+        !(assertEqualToResult (str-to-symbol (str-concat "fo" "o")) (foo))
+        !(assertEqualToResult (+ 1 (str-to-number "41")) (42))
+    "#;
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    assert_eq!(result, Ok(vec![vec![]; 7]));
+}
+
+#[test]
+fn test_string_literal_with_incorrect_escape() {
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(r#"!(str-length "\q")"#));
+
+    assert_eq!(format!("{:?}", result), r#"Ok([[(Error (str-length "\q") str-length expects argument 1 of type String, got "\q")]])"#);
+}
+
+#[test]
+fn test_list_ops() {
+    let program = "
+        (= (inc $x) (+ $x 1))
+        (= (positive $x) (> $x 0))
+
+        !(assertEqualToResult (let $l (list-range 0 3) (list-map inc $l)) ((1 2 3)))
+        !(assertEqualToResult (list-filter positive (2 -1 0 5)) ((2 5)))
+        !(assertEqualToResult (list-foldl + 0 (1 2 3 4)) (10))
+        !(assertEqualToResult (list-sort (3 1 2)) ((1 2 3)))
+        !(assertEqualToResult (let $l (list-append (a) (b c)) (list-reverse $l)) ((c b a)))
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    assert_eq!(result, Ok(vec![vec![]; 5]));
+}
+
+#[test]
+fn test_aggregate_ops() {
+    let program = "
+        (= (score) 3)
+        (= (score) 1)
+        (= (score) 3)
+        (= (weight a) 2)
+        (= (weight b) 1)
+        (= (item) a)
+        (= (item) b)
+
+        !(assertEqualToResult (count (score)) (3))
+        !(assertEqualToResult (sum (score)) (7))
+        !(assertEqualToResult (min-of (score)) (1))
+        !(assertEqualToResult (max-of (score)) (3))
+        !(assertEqualToResult (count (distinct (score))) (2))
+        !(assertEqualToResult (count (limit 2 (score))) (2))
+        !(assertEqualToResult (sort-by weight (item)) ((b a)))
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+    assert_eq!(result, Ok(vec![vec![]; 7]));
+
+    let result = metta.run(&mut SExprParser::new("!(count (superpose (1 (/ 1 0))))"));
+    assert_eq!(format!("{:?}", result), "Ok([[(Error (/ 1 0) Division by zero)]])");
+}
+
+#[test]
+fn test_number_literals_and_big_integers() {
+    let program = "
+        !(assertEqualToResult (+ 0xFF -1_000) (-745))
+        !(assertEqualToResult (* 9223372036854775807 2) (18446744073709551614))
+        !(assertEqualToResult (- 1.5e3 -2.5) (1502.5))
+        !(/ 1 0)
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    let result = result.map(|res| format!("{:?}", res));
+    assert_eq!(result, Ok("[[], [], [], [(Error (/ 1 0) Division by zero)]]".into()));
+}
+
+#[test]
+fn test_math_ops() {
+    let program = "
+        (= (hypot $x $y) (sqrt (+ (pow $x 2) (pow $y 2))))
+
+        !(assertEqualToResult (hypot 3 4) (5.0))
+        !(assertEqualToResult (max (abs -7) (round 6.5)) (7))
+        !(assertEqualToResult (isnan (log -1)) (True))
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    assert_eq!(result, Ok(vec![vec![]; 3]));
+}
+
+#[test]
+fn test_catch_and_throw() {
+    let program = "
+        (: safe-div (-> Number Number Number))
+        (= (safe-div $a $b) (catch (/ $a $b) (Error $call $reason) 0))
+        (= (check $x) (if (< $x 0) (throw (Negative $x)) $x))
+
+        !(assertEqualToResult (+ 1 (safe-div 4 0)) (1))
+        !(assertEqualToResult (catch (+ 1 (check -5)) (Error $call (Negative $x)) (fixed $x)) ((fixed -5)))
+        !(assertEqualToResult (catch (check (superpose (1 -1))) (Error $call $reason) $reason) (1 (Negative -1)))
+        !(assertEqualToResult (catch (catch (throw A) (Error $c B) b) (Error $c A) a) (a))
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    assert_eq!(result, Ok(vec![vec![]; 4]));
+}
+
+#[test]
+fn test_capabilities() {
+    let program = "
+        (= (denied $atom) (catch $atom (Error $call $reason) $reason))
+
+        !(assertEqualToResult (denied (add-atom &self (foo))) ((PermissionDenied space-mutation)))
+        !(assertEqualToResult (denied (println! hello)) ((PermissionDenied console)))
+        !(assertEqualToResult (denied (import! &self some-module.metta)) ((PermissionDenied module-loading)))
+        !(assertEqualToResult (let $s (new-state 1) (denied (change-state! $s 2))) ((PermissionDenied state-mutation)))
+        !(assertEqualToResult (let $s (new-state 1) (get-state $s)) (1))
+        !(assertEqualToResult (match &self (foo) found) ())
+    ";
+    let metta = new_metta_rust();
+    for capability in Capability::ALL {
+        metta.capabilities().borrow_mut().disable(capability);
+    }
+
+    let result = metta.run(&mut SExprParser::new(program));
+    assert_eq!(result, Ok(vec![vec![]; 6]));
+
+    metta.capabilities().borrow_mut().enable(Capability::SpaceMutation);
+    let result = metta.run(&mut SExprParser::new("!(add-atom &self (foo)) !(match &self (foo) found)"));
+    assert_eq!(format!("{:?}", result), "Ok([[], [found]])");
+}
+
+#[test]
+fn test_file_ops() {
+    let dir = std::env::temp_dir().join(format!("hyperon-test-file-ops-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = format!("
+        (= (path $name) (str-concat {:?} $name))
+        (= (denied $atom) (catch $atom (Error $call $reason) $reason))
+
+        !(file-write (path \"/data.csv\") \"a,1\\n\")
+        !(file-append (path \"/data.csv\") \"b,2\\n\")
+        !(assertEqualToResult (file-read-lines (path \"/data.csv\")) ((\"a,1\" \"b,2\")))
+        !(assertEqualToResult (file-exists (path \"/data.csv\")) (True))
+        !(assertEqualToResult (dir-list (path \"\")) ((\"data.csv\")))
+    ", dir.display().to_string());
+    let metta = new_metta_rust();
+    metta.capabilities().borrow_mut().set_sandbox_root(Some(dir.clone()));
+
+    let result = metta.run(&mut SExprParser::new(&program));
+    assert_eq!(result, Ok(vec![vec![]; 5]));
+
+    let result = metta.run(&mut SExprParser::new("!(file-exists (path \"/../outside.txt\"))"));
+    assert!(format!("{:?}", result).contains("is outside of the sandbox root"));
+
+    metta.capabilities().borrow_mut().disable(Capability::FileSystem);
+    let result = metta.run(&mut SExprParser::new("!(denied (file-read \"data.csv\"))"));
+    assert_eq!(format!("{:?}", result), "Ok([[(PermissionDenied file-system)]])");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_quote_eval_and_unify() {
+    let program = "
+        (: apply-twice (-> Atom Atom Atom))
+        (= (apply-twice $f $x) (eval ($f (eval ($f $x)))))
+        (= (inc $x) (+ $x 1))
+
+        !(assertEqualToResult (quote (+ 1 2)) ((quote (+ 1 2))))
+        !(assertEqualToResult (unquote (quote (+ 1 2))) (3))
+        !(assertEqualToResult (let $e (cons-atom + (1 2)) (eval $e)) (3))
+        !(assertEqualToResult (apply-twice inc 1) (3))
+        !(assertEqualToResult (unify (pair 1 2) (pair $a $b) (+ $a $b) none) (3))
+        !(assertEqualToResult (unify (pair 1 2) (single $a) $a none) (none))
+    ";
+    let metta = new_metta_rust();
+
+    let result = metta.run(&mut SExprParser::new(program));
+
+    assert_eq!(result, Ok(vec![vec![]; 6]));
+}
@@ -0,0 +1,32 @@
+use hyperon::*;
+use hyperon::metta::{metta_atom, metta_space};
+use hyperon::metta::runner::typed::GroundedValue;
+use hyperon::metta::interpreter::interpret;
+
+#[test]
+fn metta_macro_parses_atoms() {
+    assert_eq!(metta!{ (= (foo $x) (bar $x)) }, metta_atom("(= (foo $x) (bar $x))"));
+    assert_eq!(metta!{ (: foo-bar (-> A (List $t))) }, expr!(":" "foo-bar" ("->" "A" ("List" t))));
+    assert_eq!(metta!{ a (b c) () }, vec![sym!("a"), expr!("b" "c"), expr!()]);
+    assert_eq!(metta!{ "a\tb" 1.5 -2 0xFF }, vec!["a\tb".to_string().into_atom(), 1.5f64.into_atom(),
+        (-2i64).into_atom(), 255i64.into_atom()]);
+    assert_eq!(metta!{ (- 1) x-1 }, vec![Atom::expr([sym!("-"), 1i64.into_atom()]), sym!("x-1")]);
+    assert_eq!(metta!{}, Vec::<Atom>::new());
+}
+
+#[test]
+fn metta_macro_interpolates_rust_expressions() {
+    let x = sym!("x");
+    let sum = metta!{ (+ {40i64} {2 * 1i64}) };
+
+    assert_eq!(metta!{ (a {x.clone()} {x}) }, expr!("a" "x" "x"));
+    assert_eq!(sum, Atom::expr([sym!("+"), 40i64.into_atom(), 2i64.into_atom()]));
+}
+
+#[test]
+fn metta_macro_atoms_are_interpreted() {
+    let space = metta_space("(= (foo $x) (bar $x))");
+    let atom = metta!{ (foo {true}) };
+
+    assert_eq!(interpret(&space, &atom), Ok(vec![Atom::expr([sym!("bar"), true.into_atom()])]));
+}
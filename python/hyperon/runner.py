@@ -77,6 +77,14 @@ class MeTTa:
         os.chdir(prev_cwd)
         return result
 
+    def check_file(self, fname):
+        """Type checks all rules of the file without evaluating it and
+        returns the list of error atoms. Raises RuntimeError when the file
+        cannot be read. Expressions to be executed including !(import! ...)
+        are skipped, thus declarations of the imported modules are not
+        visible to the check; import them into this instance beforehand."""
+        return [Atom._from_catom(catom) for catom in hp.metta_check_module(self.cmetta, fname)]
+
     def run(self, program, flat=False):
         parser = SExprParser(program)
         results = hp.metta_run(self.cmetta, parser.cparser)
@@ -435,6 +435,14 @@ PYBIND11_MODULE(hyperonpy, m) {
     m.def("metta_load_module", [](CMetta metta, std::string text) {
         metta_load_module(metta.ptr, text.c_str());
     }, "Load MeTTa module");
+    m.def("metta_check_module", [](CMetta metta, std::string path) {
+            py::list atoms;
+            std::string error;
+            if (!metta_check_module(metta.ptr, path.c_str(), copy_atoms, &atoms, copy_to_string, &error)) {
+                throw std::runtime_error(error);
+            }
+            return atoms;
+        }, "Type check MeTTa module without evaluating it");
 
 }
 
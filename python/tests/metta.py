@@ -4,8 +4,16 @@ import sys
 from hyperon import MeTTa
 
 if __name__ == "__main__":
+    if len(sys.argv) < 2 or (sys.argv[1] == "--check" and len(sys.argv) < 3):
+        print("Usage: {} [--check] <file.metta>".format(sys.argv[0]))
+        sys.exit(2)
     os.system('clear')
     print("\n========= MeTTa version 0.0 =========\n\n")
     metta = MeTTa()
+    if sys.argv[1] == "--check":
+        errors = metta.check_file(sys.argv[2])
+        for error in errors:
+            print(error)
+        sys.exit(1 if errors else 0)
     for result in metta.import_file(sys.argv[1]):
         print(result)
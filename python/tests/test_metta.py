@@ -1,3 +1,5 @@
+import os
+import tempfile
 import unittest
 
 from hyperon import *
@@ -42,3 +44,22 @@ class MettaTest(unittest.TestCase):
         result = runner.run(program)
 
         self.assertEqual([[E(S('Error'), ValueAtom('String'), S('BadType'))]], result)
+
+    def test_check_file(self):
+        program = '''
+            (: foo (-> A B))
+            (: a A)
+            (: b B)
+            (= (foo a) b)
+            (= (foo b) b)
+        '''
+        with tempfile.TemporaryDirectory() as tmpdir:
+            path = os.path.join(tmpdir, 'module.metta')
+            with open(path, 'w') as f:
+                f.write(program)
+            errors = MeTTa().check_file(path)
+            self.assertEqual(1, len(errors))
+            self.assertTrue(repr(errors[0]).startswith('(Error (= (foo b) b)'))
+
+            with self.assertRaises(RuntimeError):
+                MeTTa().check_file(os.path.join(tmpdir, 'missing.metta'))